@@ -0,0 +1,74 @@
+use std::env;
+use std::thread;
+use std::time::{Duration, Instant};
+use chess_ai::selfplay::arena::Arena;
+use chess_ai::solver::mcts_solver::{MCTSSolver, MCTSSolverConfig, ParallelMode};
+use chess_ai::solver::solver::Solver;
+
+// usage: bench_mcts_threads [max threads] [simulations] [openings]
+// playouts per second of tree and root parallel MCTS for 1, 2, 4, .. threads,
+// then fixed time matches of the most threads against a single thread
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let max_threads = args.get(1).map(|x| x.parse::<usize>().unwrap()).unwrap_or(thread::available_parallelism().map(|x| x.get()).unwrap_or(1));
+    let simulations = args.get(2).map(|x| x.parse::<usize>().unwrap()).unwrap_or(5000);
+    let openings = args.get(3).map(|x| x.parse::<usize>().unwrap()).unwrap_or(0);
+
+    let make_solver = |threads: usize, parallel: ParallelMode, time_limit: Option<Duration>| {
+        MCTSSolver::new(MCTSSolverConfig {
+            simulation_count: if time_limit.is_some() { usize::MAX / 2 } else { simulations },
+            time_limit,
+            early_stop: false,
+            threads,
+            parallel,
+            ..Default::default()
+        })
+    };
+
+    let positions = Arena {
+        opening_plies: 12,
+        seed: 1000,
+        ..Default::default()
+    };
+
+    let mut thread_counts = vec![1];
+    while thread_counts.last().unwrap() * 2 <= max_threads {
+        thread_counts.push(thread_counts.last().unwrap() * 2);
+    }
+
+    for parallel in [ParallelMode::Tree, ParallelMode::Root] {
+        let mut base_rate = 0.0;
+        for &threads in thread_counts.iter() {
+            let solver = make_solver(threads, parallel, None);
+            let mut elapsed = Duration::ZERO;
+            let mut iterations = 0;
+            let mut depth = 0;
+            for index in 0..5 {
+                let (board, next_player) = positions.opening(index);
+                let start = Instant::now();
+                let result = solver.solve(&board, next_player).unwrap();
+                elapsed += start.elapsed();
+                iterations += result.nodes;
+                depth += result.depth;
+            }
+            let rate = iterations as f64 / elapsed.as_secs_f64();
+            if threads == 1 {
+                base_rate = rate;
+            }
+            println!("{:?} x{}: {:.0} iterations/s, speedup {:.2}, depth {:.1}", parallel, threads, rate, rate / base_rate, depth as f64 / 5.0);
+        }
+    }
+
+    if openings > 0 && max_threads > 1 {
+        let arena = Arena {
+            verbose: true,
+            ..Default::default()
+        };
+        let single = make_solver(1, ParallelMode::Tree, Some(Duration::from_millis(500)));
+        for parallel in [ParallelMode::Tree, ParallelMode::Root] {
+            let solver = make_solver(max_threads, parallel, Some(Duration::from_millis(500)));
+            let result = arena.play_match(&solver, &single, openings);
+            println!("{:?} x{} vs x1: +{} -{} ={} ({} adjudicated), score {:.3}", parallel, max_threads, result.wins, result.losses, result.draws, result.adjudicated, result.score());
+        }
+    }
+}
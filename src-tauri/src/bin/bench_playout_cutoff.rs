@@ -0,0 +1,60 @@
+use std::env;
+use std::time::{Duration, Instant};
+use chess_ai::selfplay::arena::Arena;
+use chess_ai::solver::mcts_solver::{MCTSSolver, MCTSSolverConfig};
+use chess_ai::solver::solver::Solver;
+
+// usage: bench_playout_cutoff [move time ms] [openings] [cutoffs..]
+// iterations per second with playouts cut off after K plies, then fixed time matches against full playouts
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let move_time = Duration::from_millis(args.get(1).map(|x| x.parse::<u64>().unwrap()).unwrap_or(500));
+    let openings = args.get(2).map(|x| x.parse::<usize>().unwrap()).unwrap_or(2);
+    let mut cutoffs: Vec<usize> = args.iter().skip(3).map(|x| x.parse::<usize>().unwrap()).collect();
+    if cutoffs.is_empty() {
+        cutoffs = vec![4, 8, 16];
+    }
+
+    let make_solver = |cutoff: Option<usize>| {
+        MCTSSolver::new(MCTSSolverConfig {
+            simulation_count: usize::MAX / 2,
+            time_limit: Some(move_time),
+            early_stop: false,
+            playout_cutoff: cutoff,
+            ..Default::default()
+        })
+    };
+
+    let positions = Arena {
+        opening_plies: 12,
+        seed: 1000,
+        ..Default::default()
+    };
+    for cutoff in [None].into_iter().chain(cutoffs.iter().map(|&x| Some(x))) {
+        let solver = make_solver(cutoff);
+        let mut elapsed = Duration::ZERO;
+        let mut iterations = 0;
+        let mut depth = 0;
+        for index in 0..5 {
+            let (board, next_player) = positions.opening(index);
+            let start = Instant::now();
+            let result = solver.solve(&board, next_player).unwrap();
+            elapsed += start.elapsed();
+            iterations += result.nodes;
+            depth += result.depth;
+        }
+        println!("cutoff {:?}: {:.0} iterations/s, depth {:.1}", cutoff, iterations as f64 / elapsed.as_secs_f64(), depth as f64 / 5.0);
+    }
+
+    let full = make_solver(None);
+    let arena = Arena {
+        max_plies: 200,
+        verbose: true,
+        ..Default::default()
+    };
+    for &cutoff in cutoffs.iter() {
+        let solver = make_solver(Some(cutoff));
+        let result = arena.play_match(&solver, &full, openings);
+        println!("cutoff {} vs full playouts: +{} -{} ={} ({} adjudicated), score {:.3}", cutoff, result.wins, result.losses, result.draws, result.adjudicated, result.score());
+    }
+}
@@ -0,0 +1,60 @@
+use std::env;
+use std::time::{Duration, Instant};
+use chess_ai::selfplay::arena::Arena;
+use chess_ai::simulator::simulator::{PlayoutPolicy, Simulator};
+use chess_ai::solver::mcts_solver::{MCTSSolver, MCTSSolverConfig};
+
+// usage: bench_playouts [move time ms] [openings] [cutoff]
+// playout speed of every playout policy, then fixed time matches against uniform playouts
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let move_time = Duration::from_millis(args.get(1).map(|x| x.parse::<u64>().unwrap()).unwrap_or(500));
+    let openings = args.get(2).map(|x| x.parse::<usize>().unwrap()).unwrap_or(2);
+    let cutoff = args.get(3).map(|x| x.parse::<usize>().unwrap());
+
+    let make_config = |playout: PlayoutPolicy| {
+        MCTSSolverConfig {
+            simulation_count: usize::MAX / 2,
+            time_limit: Some(move_time),
+            early_stop: false,
+            playout,
+            playout_cutoff: cutoff,
+            ..Default::default()
+        }
+    };
+    let policies = [PlayoutPolicy::Uniform, PlayoutPolicy::EpsilonGreedy(0.2), PlayoutPolicy::LongestJump, PlayoutPolicy::Softmax(2.0)];
+
+    let positions = Arena {
+        opening_plies: 12,
+        seed: 1000,
+        ..Default::default()
+    };
+    for policy in policies {
+        let simulator = make_config(policy).simulator();
+        let mut plies = 0;
+        let mut red_reward = 0.0;
+        let start = Instant::now();
+        for index in 0..20 {
+            let (board, next_player) = positions.opening(index);
+            for _ in 0..10 {
+                let mut played = Vec::new();
+                red_reward += simulator.simulate(&board, next_player, &mut played);
+                plies += played.len();
+            }
+        }
+        let elapsed = start.elapsed();
+        println!("{:?}: {:?} per playout, {} plies on average, red reward {:.2}", policy, elapsed / 200, plies / 200, red_reward / 200.0);
+    }
+
+    let uniform = MCTSSolver::new(make_config(PlayoutPolicy::Uniform));
+    let arena = Arena {
+        max_plies: 200,
+        verbose: true,
+        ..Default::default()
+    };
+    for policy in policies.iter().skip(1) {
+        let solver = MCTSSolver::new(make_config(*policy));
+        let result = arena.play_match(&solver, &uniform, openings);
+        println!("{:?} vs uniform: +{} -{} ={} ({} adjudicated), score {:.3}", policy, result.wins, result.losses, result.draws, result.adjudicated, result.score());
+    }
+}
@@ -0,0 +1,34 @@
+use std::env;
+use chess_ai::selfplay::arena::Arena;
+use chess_ai::solver::mcts_solver::{MCTSSolver, MCTSSolverConfig, RaveSchedule};
+
+// usage: bench_rave [simulations] [openings]
+// RAVE with either blending schedule against plain UCT, both with the same number of simulations per move
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let simulations = args.get(1).map(|x| x.parse::<usize>().unwrap()).unwrap_or(1000);
+    let openings = args.get(2).map(|x| x.parse::<usize>().unwrap()).unwrap_or(2);
+
+    let make_solver = |rave: Option<RaveSchedule>| {
+        MCTSSolver::new(MCTSSolverConfig {
+            simulation_count: simulations,
+            rave,
+            ..Default::default()
+        })
+    };
+
+    let plain = make_solver(None);
+    let arena = Arena {
+        opening_plies: 8,
+        max_plies: 200,
+        verbose: true,
+        ..Default::default()
+    };
+
+    for schedule in [RaveSchedule::Equivalence(1000.0), RaveSchedule::MinimumError(0.1)] {
+        let solver = make_solver(Some(schedule));
+        let result = arena.play_match(&solver, &plain, openings);
+        println!("{:?} vs UCT: +{} -{} ={} ({} adjudicated), score {:.3}", schedule, result.wins, result.losses, result.draws, result.adjudicated, result.score());
+        println!("avg move time {:?} vs {:?}", result.first_time / result.first_moves.max(1) as u32, result.second_time / result.second_moves.max(1) as u32);
+    }
+}
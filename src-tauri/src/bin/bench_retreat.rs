@@ -0,0 +1,54 @@
+use std::env;
+use std::time::{Duration, Instant};
+use chess_ai::evaluation::manhattan::ManhattanEvaluation;
+use chess_ai::selfplay::arena::Arena;
+use chess_ai::solver::alpha_beta_solver::{AlphaBetaSolver, AlphaBetaSolverConfig, RetreatPruning};
+use chess_ai::solver::solver::Solver;
+
+// usage: bench_retreat [depth] [openings]
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let depth = args.get(1).map(|x| x.parse::<usize>().unwrap()).unwrap_or(4);
+    let openings = args.get(2).map(|x| x.parse::<usize>().unwrap()).unwrap_or(4);
+
+    let make_solver = |mode: RetreatPruning| {
+        let config = AlphaBetaSolverConfig {
+            retreat_pruning: mode,
+            ..Default::default()
+        };
+        AlphaBetaSolver::with_config(Box::new(ManhattanEvaluation), depth, config)
+    };
+
+    // positions for the speed test, taken from random openings a bit into the game
+    let positions = Arena {
+        opening_plies: 12,
+        seed: 1000,
+        ..Default::default()
+    };
+
+    let baseline = make_solver(RetreatPruning::Off);
+    let arena = Arena {
+        verbose: true,
+        ..Default::default()
+    };
+
+    for mode in [RetreatPruning::Off, RetreatPruning::Delay, RetreatPruning::Prune] {
+        let solver = make_solver(mode);
+
+        let mut elapsed = Duration::ZERO;
+        let mut nodes = 0;
+        for index in 0..20 {
+            let (board, next_player) = positions.opening(index);
+            let start = Instant::now();
+            nodes += solver.solve(&board, next_player).unwrap().nodes;
+            elapsed += start.elapsed();
+        }
+        println!("{:?}: {:?} per position, {} nodes", mode, elapsed / 20, nodes / 20);
+
+        if mode != RetreatPruning::Off && openings > 0 {
+            let result = arena.play_match(&solver, &baseline, openings);
+            println!("{:?} vs Off: +{} -{} ={} ({} adjudicated), score {:.3}", mode, result.wins, result.losses, result.draws, result.adjudicated, result.score());
+            println!("avg move time {:?} vs {:?}", result.first_time / result.first_moves.max(1) as u32, result.second_time / result.second_moves.max(1) as u32);
+        }
+    }
+}
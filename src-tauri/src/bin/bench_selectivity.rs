@@ -0,0 +1,55 @@
+use std::env;
+use std::time::{Duration, Instant};
+use chess_ai::evaluation::manhattan::ManhattanEvaluation;
+use chess_ai::selfplay::arena::Arena;
+use chess_ai::solver::alpha_beta_solver::{AlphaBetaSolver, AlphaBetaSolverConfig};
+use chess_ai::solver::solver::Solver;
+
+// usage: bench_selectivity [depth] [openings]
+// plays late move reductions and futility pruning, alone and together, against a search with both disabled
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let depth = args.get(1).map(|x| x.parse::<usize>().unwrap()).unwrap_or(4);
+    let openings = args.get(2).map(|x| x.parse::<usize>().unwrap()).unwrap_or(4);
+
+    let make_solver = |lmr: bool, futility: bool| {
+        let config = AlphaBetaSolverConfig {
+            late_move_reduction: lmr,
+            futility_pruning: futility,
+            ..Default::default()
+        };
+        AlphaBetaSolver::with_config(Box::new(ManhattanEvaluation), depth, config)
+    };
+
+    let positions = Arena {
+        opening_plies: 12,
+        seed: 1000,
+        ..Default::default()
+    };
+
+    let baseline = make_solver(false, false);
+    let arena = Arena {
+        verbose: true,
+        ..Default::default()
+    };
+
+    for (name, lmr, futility) in [("baseline", false, false), ("lmr", true, false), ("futility", false, true), ("lmr+futility", true, true)] {
+        let solver = make_solver(lmr, futility);
+
+        let mut elapsed = Duration::ZERO;
+        let mut nodes = 0;
+        for index in 0..20 {
+            let (board, next_player) = positions.opening(index);
+            let start = Instant::now();
+            nodes += solver.solve(&board, next_player).unwrap().nodes;
+            elapsed += start.elapsed();
+        }
+        println!("{}: {:?} per position, {} nodes", name, elapsed / 20, nodes / 20);
+
+        if (lmr || futility) && openings > 0 {
+            let result = arena.play_match(&solver, &baseline, openings);
+            println!("{} vs baseline: +{} -{} ={} ({} adjudicated), score {:.3}", name, result.wins, result.losses, result.draws, result.adjudicated, result.score());
+            println!("avg move time {:?} vs {:?}", result.first_time / result.first_moves.max(1) as u32, result.second_time / result.second_moves.max(1) as u32);
+        }
+    }
+}
@@ -0,0 +1,55 @@
+use std::env;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use chess_ai::evaluation::manhattan::ManhattanEvaluation;
+use chess_ai::selfplay::arena::Arena;
+use chess_ai::solver::mcts_policy::EvaluationPolicy;
+use chess_ai::solver::mcts_solver::{MCTSSolverConfig, MCTSSolverLogic, MCTSTree, SearchState, SelectionRule, WideningSchedule};
+use chess_ai::solver::search_limits::SearchLimits;
+
+// usage: bench_widening [simulations] [positions]
+// tree size and depth with and without progressive widening, for UCB and for PUCT with evaluation priors
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let simulations = args.get(1).map(|x| x.parse::<usize>().unwrap()).unwrap_or(5000);
+    let count = args.get(2).map(|x| x.parse::<u64>().unwrap()).unwrap_or(5);
+
+    let positions = Arena {
+        opening_plies: 16,
+        seed: 2000,
+        ..Default::default()
+    };
+
+    for selection in [SelectionRule::Ucb, SelectionRule::Puct] {
+        for widening in [None, Some(WideningSchedule::default())] {
+            let config = MCTSSolverConfig {
+                simulation_count: simulations,
+                early_stop: false,
+                selection,
+                policy: Some(Arc::new(EvaluationPolicy::new(Box::new(ManhattanEvaluation), 2.0))),
+                widening,
+                ..Default::default()
+            };
+
+            let mut elapsed = Duration::ZERO;
+            let mut nodes = 0;
+            let mut depth = 0;
+            let mut branching = 0;
+            for index in 0..count {
+                let (board, next_player) = positions.opening(index);
+                branching += board.generate_actions_all(next_player).len();
+                let start = Instant::now();
+                let tree = MCTSTree::new(&board, next_player);
+                let mut logic = MCTSSolverLogic::new(config.clone());
+                let mut b = board.clone();
+                logic.expand(&tree, 0, &mut b, next_player, 0);
+                logic.uct(&tree, &SearchState::default(), simulations, &SearchLimits::default());
+                elapsed += start.elapsed();
+                nodes += tree.len();
+                depth += logic.max_depth;
+            }
+            println!("{:?} widening {}: {} nodes, depth {:.1}, {:?} per position ({} root moves on average)",
+                selection, widening.is_some(), nodes / count as usize, depth as f64 / count as f64, elapsed / count as u32, branching / count as usize);
+        }
+    }
+}
@@ -0,0 +1,38 @@
+use std::env;
+use chess_ai::book::book::OpeningBook;
+use chess_ai::book::builder::BookBuilder;
+use chess_ai::evaluation::manhattan::ManhattanEvaluation;
+use chess_ai::selfplay::arena::Arena;
+use chess_ai::solver::alpha_beta_solver::AlphaBetaSolver;
+
+// usage: build_book [plies] [games] [branching] [path]
+// grows the book at `path` (created if missing) with self-play games and a searched tree
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let plies = args.get(1).map(|x| x.parse::<usize>().unwrap()).unwrap_or(8);
+    let games = args.get(2).map(|x| x.parse::<usize>().unwrap()).unwrap_or(20);
+    let branching = args.get(3).map(|x| x.parse::<usize>().unwrap()).unwrap_or(2);
+    let path = args.get(4).cloned().unwrap_or("opening_book.json".to_string());
+
+    let book = OpeningBook::load(&path).unwrap_or_default();
+    let mut builder = BookBuilder::new(book, plies);
+
+    // fast games, the sampled opening moves provide the variety
+    let arena = Arena {
+        seed: builder.book.len() as u64,
+        ..Default::default()
+    };
+    let player = AlphaBetaSolver::new(Box::new(ManhattanEvaluation), 3);
+    builder.add_self_play(&arena, &player, games, |index, record, book| {
+        println!("game {}: {:?} after {} plies, {} positions in book", index, record.winner, record.actions.len(), book.len());
+    });
+
+    if branching > 0 {
+        let searcher = AlphaBetaSolver::new(Box::new(ManhattanEvaluation), 5);
+        builder.add_search(&searcher, arena.size, branching);
+        println!("searched tree added, {} positions in book", builder.book.len());
+    }
+
+    builder.book.save(&path).unwrap();
+    println!("written to {}", path);
+}
@@ -0,0 +1,53 @@
+use std::env;
+use std::time::Instant;
+use chess_ai::tablebase::generator::{self, GeneratorProgress};
+use chess_ai::tablebase::tablebase::TablebaseValue;
+
+// usage: build_tablebase [size] [camp] [path]
+// needs two bytes of memory per position while generating, about 300 MB for the default 5x5 with a 2x2 camp,
+// boards over `MAX_GENERATOR_BYTES` (6x6 with a 2x2 camp needs about 8.5 GB) are refused
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let size = args.get(1).map(|x| x.parse::<usize>().unwrap()).unwrap_or(5);
+    let camp = args.get(2).map(|x| x.parse::<usize>().unwrap()).unwrap_or(2);
+    let path = args.get(3).cloned().unwrap_or(format!("tablebase_{}x{}_{}.bin", size, size, camp));
+
+    let start = Instant::now();
+    let progress = |x: GeneratorProgress| match x {
+        GeneratorProgress::Terminal { positions, terminal } => println!("{} positions, {} terminal ({:?})", positions, terminal, start.elapsed()),
+        GeneratorProgress::Ply { plies, resolved } => println!("ply {}: {} positions ({:?})", plies, resolved, start.elapsed()),
+        GeneratorProgress::DistanceLimit { unresolved } => println!("distance limit reached, {} positions left as draws", unresolved),
+    };
+    let tablebase = match generator::generate_with_progress(size, camp, progress) {
+        Some(x) => x,
+        None => {
+            match generator::generator_bytes(size, camp) {
+                Some(bytes) => println!("{}x{} with a {}x{} camp needs {} MB, more than the {} MB limit", size, size, camp, camp, bytes >> 20, generator::MAX_GENERATOR_BYTES >> 20),
+                None => println!("{}x{} with a {}x{} camp: the camps overlap or there are too many positions", size, size, camp, camp),
+            }
+            return;
+        },
+    };
+
+    let mut wins = 0;
+    let mut losses = 0;
+    let mut longest = 0;
+    for &value in tablebase.values.iter() {
+        match TablebaseValue::decode(value) {
+            TablebaseValue::Win(plies) => {
+                wins += 1;
+                longest = longest.max(plies);
+            },
+            TablebaseValue::Loss(plies) => {
+                losses += 1;
+                longest = longest.max(plies);
+            },
+            TablebaseValue::Draw => (),
+        }
+    }
+    let total = tablebase.values.len();
+    println!("wins {}, losses {}, draws {}, longest {} plies", wins, losses, total - wins - losses, longest);
+
+    tablebase.save(&path).unwrap();
+    println!("written to {}", path);
+}
@@ -3,11 +3,19 @@
   windows_subsystem = "windows"
 )]
 
-use chess_ai::command::chess1::chess1_solve;
+use chess_ai::command::chess1::{chess1_mcts_best, chess1_mcts_solve, chess1_ponder_start, chess1_ponder_stop, chess1_set_difficulty, chess1_solve, chess1_stop};
 
 fn main() {
   tauri::Builder::default()
-      .invoke_handler(tauri::generate_handler![chess_ai::command::chess1::chess1_solve])
+      .invoke_handler(tauri::generate_handler![
+          chess_ai::command::chess1::chess1_solve,
+          chess_ai::command::chess1::chess1_stop,
+          chess_ai::command::chess1::chess1_ponder_start,
+          chess_ai::command::chess1::chess1_ponder_stop,
+          chess_ai::command::chess1::chess1_set_difficulty,
+          chess_ai::command::chess1::chess1_mcts_solve,
+          chess_ai::command::chess1::chess1_mcts_best,
+      ])
       .run(tauri::generate_context!())
       .expect("error while running tauri application");
 }
@@ -0,0 +1,86 @@
+use std::env;
+use std::time::{Duration, Instant};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use chess_ai::board::board::Board;
+use chess_ai::constants::{EMPTY, RED};
+use chess_ai::solver::camp_planner::CampPlanner;
+use chess_ai::solver::search_limits::SearchLimits;
+
+// usage: planner_stats [positions] [retreats]
+// red-only positions made by retreating `retreats` random moves out of a filled camp,
+// compares the optimal plan with the greedy one
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let positions = args.get(1).map(|x| x.parse::<u64>().unwrap()).unwrap_or(20);
+    let retreats = args.get(2).map(|x| x.parse::<usize>().unwrap()).unwrap_or(8);
+
+    let planner = CampPlanner::new(2_000_000);
+    let limits = SearchLimits::default();
+
+    let mut solved = 0;
+    let mut optimal_total = 0;
+    let mut greedy_total = 0;
+    // optimal lengths of the positions greedy solved, for the average overhead
+    let mut compared_total = 0;
+    let mut greedy_optimal = 0;
+    let mut greedy_failed = 0;
+    let mut worst = 0;
+    let mut elapsed = Duration::ZERO;
+    let mut nodes = 0;
+
+    for index in 0..positions {
+        let mut rng = StdRng::seed_from_u64(index);
+        let mut board = Board::new(9);
+        for i in 0..board.size {
+            for j in 0..board.size {
+                board.data[i][j] = if board.is_in_goal(RED, i, j) { RED } else { EMPTY };
+            }
+        }
+        for _ in 0..retreats {
+            let mut actions: Vec<_> = board.generate_actions_all(RED).into_iter().filter(|a| a.gain(RED) < 0).collect();
+            // move generation order is unspecified, sort so that the seed alone decides the position
+            actions.sort_by_key(|a| (a.from_x, a.from_y, a.to_x, a.to_y));
+            if actions.is_empty() {
+                break;
+            }
+            board.perform_action(actions[rng.gen_range(0..actions.len())]);
+        }
+
+        let start = Instant::now();
+        let plan = match planner.plan(&board, RED, &limits) {
+            Some(x) => x,
+            None => {
+                println!("#{}: out of budget", index);
+                continue;
+            },
+        };
+        elapsed += start.elapsed();
+        nodes += plan.nodes;
+        solved += 1;
+
+        let optimal = plan.actions.len();
+        optimal_total += optimal;
+        match planner.greedy_plan(&board, RED, 4 * optimal + 20) {
+            Some(greedy) => {
+                println!("#{}: bound {}, optimal {}, greedy {}", index, planner.lower_bound(&board, RED), optimal, greedy.len());
+                greedy_total += greedy.len();
+                compared_total += optimal;
+                worst = worst.max(greedy.len() - optimal);
+                if greedy.len() == optimal {
+                    greedy_optimal += 1;
+                }
+            },
+            None => {
+                println!("#{}: bound {}, optimal {}, greedy failed", index, planner.lower_bound(&board, RED), optimal);
+                greedy_failed += 1;
+            },
+        }
+    }
+
+    println!("solved {}/{}, avg optimal {:.1} moves, {:?} and {} nodes per position", solved, positions, optimal_total as f64 / solved.max(1) as f64, elapsed / solved.max(1) as u32, nodes / solved.max(1) as u64);
+    println!("greedy optimal in {}/{}, failed in {}, worst +{} moves", greedy_optimal, solved, greedy_failed, worst);
+    if compared_total > 0 {
+        println!("greedy plans {:.1}% longer on average", 100.0 * greedy_total as f64 / compared_total as f64 - 100.0);
+    }
+}
@@ -1,21 +1,35 @@
 use chess_ai::board::board::Board;
-use chess_ai::solver::solver::{MaybeSolver, Solver};
 use chess_ai::constants::RED;
 use chess_ai::board::action::Action;
+use std::env;
 use std::io::stdin;
+use std::sync::Arc;
 use chess_ai::solver::brute_force_search_solver::BruteForceSearchSolver;
+use chess_ai::solver::mcts_solver::MCTSEngine;
 use chess_ai::solver::mix_solver::MixSolver;
+use chess_ai::solver::ponder::Ponderer;
+use chess_ai::solver::solver::Solver;
 
 fn main() {
     let mut board = Board::new(9);
     println!("{}", board);
-    let solver = MixSolver::default();
+    // the engine thinks about the reply it expects while waiting for input
+    // `test mcts` plays with the MCTS engine, which keeps its tree between moves
+    let solver: Arc<dyn Solver + Send + Sync> = if env::args().any(|x| x == "mcts") {
+        Arc::new(MCTSEngine::new(Default::default()))
+    } else {
+        Arc::new(MixSolver::default())
+    };
+    let mut ponderer = Ponderer::new(solver);
     
     while board.is_game_over().is_none() {
-        let action = solver.solve(&board, RED);
-        board.perform_action(action);
-        println!("AI:");
+        let result = ponderer.solve(&board, RED).unwrap();
+        board.perform_action(result.best_move);
+        println!("AI: {} ({}, depth {}, {} nodes)", result.best_move, result.describe(), result.depth, result.nodes);
         println!("{}", board);
+        if let Some(&predicted) = result.pv.get(1) {
+            ponderer.start(&board, RED, predicted);
+        }
     
         let mut input = String::new();
         stdin().read_line(&mut input);
@@ -27,6 +27,17 @@ impl Action {
             self.to_x <= self.from_x && self.to_y <= self.from_y
         }
     }
+
+    // manhattan progress towards the opponent's corner, negative for retreats
+    pub fn gain(&self, player: usize) -> i32 {
+        let dx = self.to_x as i32 - self.from_x as i32;
+        let dy = self.to_y as i32 - self.from_y as i32;
+        if player == RED {
+            dx + dy
+        } else {
+            -dx - dy
+        }
+    }
 }
 
 impl Display for Action {
@@ -5,12 +5,19 @@ use crate::board::action::Action;
 use crate::constants::{BLUE, DIR4, DIR4_JUMP2, EMPTY, RED};
 use serde::{Serialize, Deserialize};
 
-#[derive(Clone, Serialize, Deserialize)]
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Board {
     pub size: usize,
+    // side length of the square camps in opposite corners, boards sent without it use the standard 4
+    #[serde(default = "default_camp")]
+    pub camp: usize,
     pub data: Vec<Vec<usize>>,
 }
 
+fn default_camp() -> usize {
+    4
+}
+
 impl Display for Board {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         for i in 0..self.size {
@@ -32,13 +39,19 @@ impl Display for Board {
 
 impl Board {
     pub fn new(size: usize) -> Board {
+        Board::with_camp(size, 4)
+    }
+
+    // a smaller game, e.g. 6x6 with 3x3 camps
+    pub fn with_camp(size: usize, camp: usize) -> Board {
         let mut b = Board {
             size,
+            camp,
             data: vec![vec![0; size]; size]
         };
 
-        for i in 0..4 {
-            for j in 0..4 {
+        for i in 0..camp {
+            for j in 0..camp {
                 b.data[i][j] = RED;
                 b.data[size - i - 1][size - j - 1] = BLUE;
             }
@@ -48,8 +61,8 @@ impl Board {
     }
 
     pub fn is_red_winning(&self) -> bool {
-        for i in 0..4 {
-            for j in 0..4 {
+        for i in 0..self.camp {
+            for j in 0..self.camp {
                 if self.data[self.size - i - 1][self.size - j - 1] != RED {
                     return false;
                 }
@@ -59,8 +72,8 @@ impl Board {
     }
 
     pub fn is_blue_winning(&self) -> bool {
-        for i in 0..4 {
-            for j in 0..4 {
+        for i in 0..self.camp {
+            for j in 0..self.camp {
                 if self.data[i][j] != BLUE {
                     return false;
                 }
@@ -69,6 +82,40 @@ impl Board {
         true
     }
 
+    // true if (x, y) is in the camp `player` has to fill
+    pub fn is_in_goal(&self, player: usize, x: usize, y: usize) -> bool {
+        if player == RED {
+            x >= self.size - self.camp && y >= self.size - self.camp
+        } else {
+            x < self.camp && y < self.camp
+        }
+    }
+
+    pub fn pieces_outside_goal(&self, player: usize) -> usize {
+        let mut result = 0;
+        for i in 0..self.size {
+            for j in 0..self.size {
+                if self.data[i][j] == player && !self.is_in_goal(player, i, j) {
+                    result += 1;
+                }
+            }
+        }
+        result
+    }
+
+    // a copy with every piece of the other player removed
+    pub fn keep_player(&self, player: usize) -> Board {
+        let mut ret = self.clone();
+        for i in 0..self.size {
+            for j in 0..self.size {
+                if ret.data[i][j] != player {
+                    ret.data[i][j] = EMPTY;
+                }
+            }
+        }
+        ret
+    }
+
     pub fn is_game_over(&self) -> Option<usize> {
         if self.is_red_winning() {
             Some(RED)
@@ -225,7 +272,7 @@ impl Board {
                 if cell == RED {
                     for k in 2..4 {
                         let nx1 = i as i32 + DIR4[k][0];
-                        let ny1 = j as i32 + DIR4[k][0];
+                        let ny1 = j as i32 + DIR4[k][1];
                         if nx1 >= 0 && nx1 < self.size as i32 && ny1 >= 0 && ny1 < self.size as i32 {
                             let nx1 = nx1 as usize;
                             let ny1 = ny1 as usize;
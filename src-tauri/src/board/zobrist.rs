@@ -0,0 +1,50 @@
+use crate::board::board::Board;
+use crate::constants::{BLUE, EMPTY};
+
+// zobrist keys for boards up to 10x10, generated at compile time so hashes are identical between runs
+
+const fn splitmix64(state: u64) -> (u64, u64) {
+    let state = state.wrapping_add(0x9E3779B97F4A7C15);
+    let mut z = state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+    (state, z ^ (z >> 31))
+}
+
+const fn build_piece_keys() -> [[[u64; 2]; 10]; 10] {
+    let mut keys = [[[0_u64; 2]; 10]; 10];
+    let mut state = 0x2545F4914F6CDD1D_u64;
+    let mut i = 0;
+    while i < 10 {
+        let mut j = 0;
+        while j < 10 {
+            let mut k = 0;
+            while k < 2 {
+                let (next_state, key) = splitmix64(state);
+                state = next_state;
+                keys[i][j][k] = key;
+                k += 1;
+            }
+            j += 1;
+        }
+        i += 1;
+    }
+    keys
+}
+
+// indexed by [x][y][player - 1]
+pub const PIECE_KEYS: [[[u64; 2]; 10]; 10] = build_piece_keys();
+pub const BLUE_TO_MOVE_KEY: u64 = splitmix64(0x5851F42D4C957F2D).1;
+
+pub fn hash(board: &Board, next_player: usize) -> u64 {
+    let mut h = if next_player == BLUE { BLUE_TO_MOVE_KEY } else { 0 };
+    for i in 0..board.size {
+        for j in 0..board.size {
+            let cell = board.data[i][j];
+            if cell != EMPTY {
+                h ^= PIECE_KEYS[i][j][cell - 1];
+            }
+        }
+    }
+    h
+}
@@ -0,0 +1,86 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+use rand::Rng;
+use serde::{Serialize, Deserialize};
+use crate::board::action::Action;
+use crate::board::board::Board;
+use crate::board::zobrist;
+
+#[derive(Clone, Copy, Serialize, Deserialize, Debug)]
+pub struct BookMove {
+    pub action: Action,
+    pub weight: u32,
+}
+
+// position (zobrist hash with the side to move) -> weighted moves, stored as json
+#[derive(Default, Serialize, Deserialize)]
+pub struct OpeningBook {
+    pub entries: HashMap<u64, Vec<BookMove>>,
+}
+
+impl OpeningBook {
+    pub fn new() -> OpeningBook {
+        Default::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    // adds `weight` to the move, inserting it if it is new
+    pub fn add(&mut self, board: &Board, next_player: usize, action: Action, weight: u32) {
+        let moves = self.entries.entry(zobrist::hash(board, next_player)).or_default();
+        match moves.iter_mut().find(|x| x.action == action) {
+            Some(x) => x.weight += weight,
+            None => moves.push(BookMove { action, weight }),
+        }
+    }
+
+    pub fn moves(&self, board: &Board, next_player: usize) -> Option<&[BookMove]> {
+        self.entries.get(&zobrist::hash(board, next_player)).map(|x| x.as_slice())
+    }
+
+    // the heaviest move, ties go to the first one added
+    pub fn best(&self, board: &Board, next_player: usize) -> Option<Action> {
+        let moves = self.moves(board, next_player)?;
+        let mut best: Option<BookMove> = None;
+        for &x in moves.iter() {
+            if x.weight > 0 && best.map_or(true, |b| x.weight > b.weight) {
+                best = Some(x);
+            }
+        }
+        best.map(|x| x.action)
+    }
+
+    // a move drawn with probability proportional to its weight
+    pub fn pick(&self, board: &Board, next_player: usize, rng: &mut impl Rng) -> Option<Action> {
+        let moves = self.moves(board, next_player)?;
+        let total: u32 = moves.iter().map(|x| x.weight).sum();
+        if total == 0 {
+            return None;
+        }
+        let mut r = rng.gen_range(0..total);
+        for x in moves.iter() {
+            if r < x.weight {
+                return Some(x.action);
+            }
+            r -= x.weight;
+        }
+        None
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, serde_json::to_string(self)?)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> io::Result<OpeningBook> {
+        let data = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&data)?)
+    }
+}
@@ -0,0 +1,130 @@
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use crate::board::action::Action;
+use crate::board::board::Board;
+use crate::book::book::OpeningBook;
+use crate::constants::RED;
+use crate::evaluation::manhattan::ManhattanEvaluation;
+use crate::selfplay::arena::{Arena, GameRecord};
+use crate::solver::mcts_policy::{EvaluationPolicy, PolicySource};
+use crate::solver::solver::Solver;
+
+// grows an opening book, only the first `max_plies` plies of a game are recorded
+pub struct BookBuilder {
+    pub book: OpeningBook,
+    pub max_plies: usize,
+    // samples the opening moves of self-play games
+    pub policy: EvaluationPolicy,
+}
+
+impl BookBuilder {
+    pub fn new(book: OpeningBook, max_plies: usize) -> BookBuilder {
+        BookBuilder {
+            book,
+            max_plies,
+            policy: EvaluationPolicy::new(Box::new(ManhattanEvaluation), 2.0),
+        }
+    }
+
+    // the winner's moves of a game played from `Board::new(size)` gain a weight each, drawn games add nothing
+    pub fn add_game(&mut self, size: usize, actions: &[Action], winner: Option<usize>) {
+        let winner = match winner {
+            Some(x) => x,
+            None => return,
+        };
+        let mut board = Board::new(size);
+        let mut next_player = RED;
+        for &action in actions.iter().take(self.max_plies) {
+            if next_player == winner {
+                self.book.add(&board, next_player, action, 1);
+            }
+            board.perform_action(action);
+            next_player = 3 - next_player;
+        }
+    }
+
+    // self-play from the start position, good opening moves collect weight as they keep winning
+    // the first `arena.opening_plies` moves are sampled to vary the games, `progress` is called after each game
+    pub fn add_self_play(&mut self, arena: &Arena, solver: &dyn Solver, games: usize, mut progress: impl FnMut(u64, &GameRecord, &OpeningBook)) {
+        for index in 0..games as u64 {
+            let mut rng = StdRng::seed_from_u64(arena.seed.wrapping_add(index));
+            let mut board = Board::new(arena.size);
+            let mut next_player = RED;
+            let mut actions = Vec::new();
+            while actions.len() < arena.opening_plies && board.is_game_over().is_none() {
+                let action = match self.sample_move(&board, next_player, &mut rng) {
+                    Some(x) => x,
+                    None => break,
+                };
+                board.perform_action(action);
+                actions.push(action);
+                next_player = 3 - next_player;
+            }
+
+            let mut record = arena.play_from(solver, solver, board, next_player);
+            actions.extend_from_slice(&record.actions);
+            record.actions = actions;
+            self.add_game(arena.size, &record.actions, record.winner);
+            progress(index, &record, &self.book);
+        }
+    }
+
+    // half from the policy and, where the book knows the position, half from the book's weights
+    // so the book's moves are preferred while new moves keep being tried
+    fn sample_move(&self, board: &Board, next_player: usize, rng: &mut impl Rng) -> Option<Action> {
+        let mut actions = board.generate_actions_all(next_player);
+        if actions.is_empty() {
+            return None;
+        }
+        // sort first, the move generator's order is not stable between runs
+        actions.sort_by_key(|a| (a.from_x, a.from_y, a.to_x, a.to_y));
+        let mut weights = self.policy.priors(board, next_player, &actions);
+        if let Some(moves) = self.book.moves(board, next_player) {
+            let total: u32 = moves.iter().map(|x| x.weight).sum();
+            if total > 0 {
+                for (weight, action) in weights.iter_mut().zip(actions.iter()) {
+                    let book_weight = moves.iter().find(|x| x.action == *action).map_or(0, |x| x.weight);
+                    *weight = (*weight + book_weight as f64 / total as f64) / 2.0;
+                }
+            }
+        }
+
+        let mut r = rng.gen::<f64>() * weights.iter().sum::<f64>();
+        for (weight, &action) in weights.iter().zip(actions.iter()) {
+            if r < *weight {
+                return Some(action);
+            }
+            r -= weight;
+        }
+        actions.last().copied()
+    }
+
+    // searches every position of a tree from the start, following the searched move and the
+    // `branching - 1` biggest forward moves besides it, and records each searched move
+    pub fn add_search(&mut self, solver: &dyn Solver, size: usize, branching: usize) {
+        let mut board = Board::new(size);
+        self.search_tree(solver, &mut board, RED, 0, branching);
+    }
+
+    fn search_tree(&mut self, solver: &dyn Solver, board: &mut Board, next_player: usize, ply: usize, branching: usize) {
+        if ply >= self.max_plies || board.is_game_over().is_some() {
+            return;
+        }
+        let best = match solver.solve(board, next_player) {
+            Ok(x) => x.best_move,
+            Err(_) => return,
+        };
+        self.book.add(board, next_player, best, 1);
+
+        let mut actions = board.generate_actions_all(next_player);
+        actions.sort_by_key(|a| (-a.gain(next_player), a.from_x, a.from_y, a.to_x, a.to_y));
+        let mut children = vec![best];
+        children.extend(actions.into_iter().filter(|&a| a != best).take(branching.saturating_sub(1)));
+
+        for action in children {
+            board.perform_action(action);
+            self.search_tree(solver, board, 3 - next_player, ply + 1, branching);
+            board.undo_action(action);
+        }
+    }
+}
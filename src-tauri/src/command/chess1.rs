@@ -1,15 +1,104 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use crate::board::action::Action;
 use crate::board::board::Board;
-use crate::solver::alpha_beta_solver::AlphaBetaSolver;
-use crate::solver::mcts_solver::MCTSSolver;
+use crate::book::book::OpeningBook;
+use crate::solver::book_solver::BookSolver;
+use crate::solver::difficulty_solver::Difficulty;
+use crate::solver::mcts_solver::MCTSEngine;
 use crate::solver::mix_solver::MixSolver;
-use crate::solver::solver::Solver;
+use crate::solver::ponder::Ponderer;
+use crate::solver::search_limits::{CancellationToken, SearchLimits};
+use crate::solver::solver::{SearchResult, Solver, SolverError};
 
-#[tauri::command]
-pub async fn chess1_solve(board: Board, next_player: usize) -> Action {
+const BOOK_PATH: &str = "opening_book.json";
+
+// keeps its tree between `chess1_mcts_solve` calls
+static MCTS_ENGINE: Mutex<Option<Arc<MCTSEngine>>> = Mutex::new(None);
+
+fn mcts_engine() -> Arc<MCTSEngine> {
+    MCTS_ENGINE.lock().unwrap().get_or_insert_with(|| Arc::new(MCTSEngine::new(Default::default()))).clone()
+}
+
+// token of the search currently running in `chess1_solve`, cancelled by `chess1_stop`
+static CURRENT_SEARCH: Mutex<Option<CancellationToken>> = Mutex::new(None);
+// the engine lives between commands so that pondering and its transposition table carry over
+static PONDERER: Mutex<Option<Ponderer>> = Mutex::new(None);
+
+fn with_ponderer<T>(f: impl FnOnce(&mut Ponderer) -> T) -> T {
+    let mut ponderer = PONDERER.lock().unwrap();
     // let solver = MCTSSolver::new(Default::default());
     // let solver = AlphaBetaSolver::default();
-    let solver = MixSolver::default();
-    let action = solver.solve(&board, next_player);
-    action
+    let ponderer = ponderer.get_or_insert_with(|| Ponderer::new(engine()));
+    f(ponderer)
+}
+
+// `MixSolver`, behind the opening book when one has been built next to the app
+fn engine() -> Arc<dyn Solver + Send + Sync> {
+    match OpeningBook::load(BOOK_PATH) {
+        Ok(book) => Arc::new(BookSolver::weighted(Arc::new(book), Box::new(MixSolver::default()), rand::random())),
+        Err(_) => Arc::new(MixSolver::default()),
+    }
+}
+
+// used by later `chess1_solve` calls, the full strength engine is the default
+#[tauri::command]
+pub fn chess1_set_difficulty(difficulty: Difficulty) {
+    with_ponderer(|ponderer| {
+        ponderer.stop();
+        ponderer.solver = match difficulty {
+            Difficulty::Expert => engine(),
+            _ => difficulty.solver(rand::random()),
+        };
+    });
+}
+
+#[tauri::command]
+pub async fn chess1_solve(board: Board, next_player: usize, time_limit_ms: Option<u64>) -> Result<SearchResult, SolverError> {
+    let limits = match time_limit_ms {
+        Some(x) => SearchLimits::with_time(Duration::from_millis(x)),
+        None => SearchLimits::default(),
+    };
+    *CURRENT_SEARCH.lock().unwrap() = Some(limits.cancel.clone());
+
+    // the lock is only held to take the search over, so the ponder and stop commands are not blocked meanwhile
+    let search = with_ponderer(|ponderer| ponderer.take(&board, next_player));
+    search.finish(&board, next_player, &limits)
+}
+
+// like `chess1_solve` with the MCTS engine, statistics of earlier searches carry over to the next move
+#[tauri::command]
+pub async fn chess1_mcts_solve(board: Board, next_player: usize, time_limit_ms: Option<u64>) -> Result<SearchResult, SolverError> {
+    let limits = match time_limit_ms {
+        Some(x) => SearchLimits::with_time(Duration::from_millis(x)),
+        None => SearchLimits::default(),
+    };
+    *CURRENT_SEARCH.lock().unwrap() = Some(limits.cancel.clone());
+
+    mcts_engine().solve_with_limits(&board, next_player, &limits)
+}
+
+// best move so far of a running `chess1_mcts_solve`, or the result of the last one
+#[tauri::command]
+pub fn chess1_mcts_best() -> Option<SearchResult> {
+    mcts_engine().report.get()
+}
+
+// makes a running `chess1_solve` return its best move so far
+#[tauri::command]
+pub fn chess1_stop() {
+    if let Some(token) = CURRENT_SEARCH.lock().unwrap().take() {
+        token.cancel();
+    }
+}
+
+// `board` is the position after the engine's move, `predicted` the reply the engine expects (the second move of its pv)
+#[tauri::command]
+pub fn chess1_ponder_start(board: Board, next_player: usize, predicted: Action) {
+    with_ponderer(|ponderer| ponderer.start(&board, next_player, predicted));
+}
+
+#[tauri::command]
+pub fn chess1_ponder_stop() {
+    with_ponderer(|ponderer| ponderer.stop());
 }
@@ -1,5 +1,7 @@
 use crate::board::board::Board;
+use crate::evaluation::score::Score;
 
-pub trait Evaluation {
-    fn evaluate(&self, board: &Board, next_player: usize) -> f64;
-}
\ No newline at end of file
+pub trait Evaluation: Send + Sync {
+    // finished games score +-WIN_SCORE, the search adjusts them by distance
+    fn evaluate(&self, board: &Board, next_player: usize) -> Score;
+}
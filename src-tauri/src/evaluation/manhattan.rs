@@ -2,6 +2,7 @@ use smallvec::SmallVec;
 use crate::board::board::Board;
 use crate::constants::{BLUE, EMPTY, RED};
 use crate::evaluation::evaluation::Evaluation;
+use crate::evaluation::score::{Score, WIN_SCORE};
 
 pub struct ManhattanEvaluation;
 
@@ -19,21 +20,22 @@ impl ManhattanEvaluation {
 
         let mut result = 0;
         let size = board.size;
+        let camp = board.camp;
         // let mut iter = 0_usize;
 
         for i in 0..size {
             for j in 0..size {
-                if i >= size - 4 && j >= size - 4 {
+                if i >= size - camp && j >= size - camp {
                     continue;
                 }
                 if board.data[i][j] == RED {
                     // result += empty_cells[iter].0 as i32 - i as i32 + empty_cells[iter].1 as i32 - j as i32;
                     // iter += 1;
-                    if i < size - 4 {
-                        result += size - 4 - i;
+                    if i < size - camp {
+                        result += size - camp - i;
                     }
-                    if j < size - 4 {
-                        result += size - 4 - j;
+                    if j < size - camp {
+                        result += size - camp - j;
                     }
                 }
             }
@@ -46,18 +48,19 @@ impl ManhattanEvaluation {
     pub fn dis_blue(&self, board: &Board) -> usize {
         let mut result = 0;
         let size = board.size;
+        let camp = board.camp;
 
         for i in 0..size {
             for j in 0..size {
-                if i < 4 && j < 4 {
+                if i < camp && j < camp {
                     continue;
                 }
                 if board.data[i][j] == BLUE {
-                    if i >= 4 {
-                        result += i - 3;
+                    if i >= camp {
+                        result += i + 1 - camp;
                     }
-                    if j >= 4 {
-                        result += j - 3;
+                    if j >= camp {
+                        result += j + 1 - camp;
                     }
                 }
             }
@@ -69,12 +72,14 @@ impl ManhattanEvaluation {
     pub fn dis_red2(&self, board: &Board) -> usize {
         let mut result = 0.0;
         let size = board.size;
+        // center of the camp in the far corner
+        let center = size as f64 - 1.0 - (board.camp as f64 - 1.0) / 2.0;
 
         for i in 0..size {
             for j in 0..size {
                 if board.data[i][j] == RED {
-                    result += (size as f64 - 2.5 - j as f64).abs();
-                    result += (size as f64 - 2.5 - i as f64).abs();
+                    result += (center - j as f64).abs();
+                    result += (center - i as f64).abs();
                 }
             }
         }
@@ -85,12 +90,13 @@ impl ManhattanEvaluation {
     pub fn dis_blue2(&self, board: &Board) -> usize {
         let mut result = 0.0;
         let size = board.size;
+        let center = (board.camp as f64 - 1.0) / 2.0;
 
         for i in 0..size {
             for j in 0..size {
                 if board.data[i][j] == BLUE {
-                    result += (i as f64 - 1.5).abs();
-                    result += (j as f64 - 1.5).abs();
+                    result += (i as f64 - center).abs();
+                    result += (j as f64 - center).abs();
                 }
             }
         }
@@ -101,43 +107,48 @@ impl ManhattanEvaluation {
     pub fn bonus_red(&self, board: &Board) -> usize {
         let mut ans = 0;
         let size = board.size;
-        ans += if board.data[size - 1][size - 1] == RED { 3 } else { 0 };
-        ans += if board.data[size - 4][size - 1] == RED { 2 } else { 0 };
-        ans += if board.data[size - 4][size - 4] == RED { 2 } else { 0 };
-        ans += if board.data[size - 1][size - 4] == RED { 2 } else { 0 };
-        ans += if board.data[size - 2][size - 1] == RED { 1 } else { 0 };
-        ans += if board.data[size - 3][size - 1] == RED { 1 } else { 0 };
-        ans += if board.data[size - 1][size - 2] == RED { 1 } else { 0 };
-        ans += if board.data[size - 1][size - 3] == RED { 1 } else { 0 };
-        ans += if board.data[size - 4][size - 2] == RED { 1 } else { 0 };
-        ans += if board.data[size - 4][size - 3] == RED { 1 } else { 0 };
-        ans += if board.data[size - 2][size - 4] == RED { 1 } else { 0 };
-        ans += if board.data[size - 3][size - 4] == RED { 1 } else { 0 };
+        for a in 0..board.camp {
+            for b in 0..board.camp {
+                if board.data[size - 1 - a][size - 1 - b] == RED {
+                    ans += camp_bonus(a, b, board.camp);
+                }
+            }
+        }
 
         ans
     }
 
     pub fn bonus_blue(&self, board: &Board) -> usize {
         let mut ans = 0;
-        ans += if board.data[0][0] == RED { 3 } else { 0 };
-        ans += if board.data[0][3] == RED { 2 } else { 0 };
-        ans += if board.data[3][3] == RED { 2 } else { 0 };
-        ans += if board.data[3][0] == RED { 2 } else { 0 };
-        ans += if board.data[1][0] == RED { 1 } else { 0 };
-        ans += if board.data[2][0] == RED { 1 } else { 0 };
-        ans += if board.data[0][1] == RED { 1 } else { 0 };
-        ans += if board.data[0][2] == RED { 1 } else { 0 };
-        ans += if board.data[3][1] == RED { 1 } else { 0 };
-        ans += if board.data[3][2] == RED { 1 } else { 0 };
-        ans += if board.data[1][3] == RED { 1 } else { 0 };
-        ans += if board.data[2][3] == RED { 1 } else { 0 };
+        for a in 0..board.camp {
+            for b in 0..board.camp {
+                if board.data[a][b] == RED {
+                    ans += camp_bonus(a, b, board.camp);
+                }
+            }
+        }
 
         ans
     }
 }
 
+// weight of the camp cell `a` rows and `b` columns in from the far corner:
+// 3 for the far corner, 2 for the other corners of the camp, 1 along its edges, nothing inside
+fn camp_bonus(a: usize, b: usize, camp: usize) -> usize {
+    let last = camp - 1;
+    if a == 0 && b == 0 {
+        3
+    } else if (a == 0 || a == last) && (b == 0 || b == last) {
+        2
+    } else if a == 0 || b == 0 || a == last || b == last {
+        1
+    } else {
+        0
+    }
+}
+
 impl Evaluation for ManhattanEvaluation {
-    fn evaluate(&self, board: &Board, next_player: usize) -> f64 {
+    fn evaluate(&self, board: &Board, next_player: usize) -> Score {
         // let size = board.size;
         // let total = (size - 4) * 2 * 16;
         //
@@ -145,9 +156,9 @@ impl Evaluation for ManhattanEvaluation {
         match board.is_game_over() {
             Some(x) => {
                 return if (x == RED && next_player == RED) || (x == BLUE && next_player == BLUE) {
-                    f64::INFINITY
+                    WIN_SCORE
                 } else {
-                    -f64::INFINITY
+                    -WIN_SCORE
                 }
             },
             _ => ()
@@ -169,6 +180,6 @@ impl Evaluation for ManhattanEvaluation {
             bonus_blue as i32 - bonus_red as i32
         };
 
-        (v + bonus) as f64
+        v + bonus
     }
 }
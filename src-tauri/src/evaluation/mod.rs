@@ -1,3 +1,4 @@
 pub mod manhattan;
 pub mod evaluation;
-pub mod naive_bonus;
\ No newline at end of file
+pub mod naive_bonus;
+pub mod score;
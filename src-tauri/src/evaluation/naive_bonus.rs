@@ -1,5 +1,6 @@
 use crate::{board::board::Board, constants::{RED, BLUE}};
 use super::evaluation::Evaluation;
+use super::score::Score;
 
 pub struct NaiveBonusEvaluation {
     pub score0: usize,
@@ -113,16 +114,14 @@ impl NaiveBonusEvaluation {
 }
 
 impl Evaluation for NaiveBonusEvaluation {
-    fn evaluate(&self, board: &Board, next_player: usize) -> f64 {
-        let red_score = self.get_red_score(board);
-        let blue_score = self.get_blue_score(board);
+    fn evaluate(&self, board: &Board, next_player: usize) -> Score {
+        let red_score = self.get_red_score(board) as Score;
+        let blue_score = self.get_blue_score(board) as Score;
 
-        let value = if next_player == RED {
+        if next_player == RED {
             red_score - blue_score
         } else {
             blue_score - red_score
-        };
-
-        value as f64
+        }
     }
 }
@@ -0,0 +1,65 @@
+// integer scores, finished games are encoded by their distance so that faster wins and slower losses score higher
+
+pub type Score = i32;
+
+// the side to move has already won
+pub const WIN_SCORE: Score = 1_000_000;
+// any score beyond this is a win or loss at a known distance
+pub const WIN_BOUND: Score = WIN_SCORE - 10_000;
+// strictly above every real score, used as the open end of search windows
+pub const INFINITE_SCORE: Score = WIN_SCORE + 1;
+
+// score of a position won `ply` plies after the root
+pub fn win_in(ply: usize) -> Score {
+    WIN_SCORE - ply as Score
+}
+
+pub fn loss_in(ply: usize) -> Score {
+    -WIN_SCORE + ply as Score
+}
+
+pub fn is_win(score: Score) -> bool {
+    score >= WIN_BOUND
+}
+
+pub fn is_loss(score: Score) -> bool {
+    score <= -WIN_BOUND
+}
+
+pub fn is_decisive(score: Score) -> bool {
+    is_win(score) || is_loss(score)
+}
+
+// moves of the winning side until the game ends, positive for wins and negative for losses
+pub fn moves_to_end(score: Score) -> Option<i32> {
+    if is_win(score) {
+        let plies = WIN_SCORE - score;
+        Some((plies + 1) / 2)
+    } else if is_loss(score) {
+        let plies = WIN_SCORE + score;
+        Some(-(plies + 1) / 2)
+    } else {
+        None
+    }
+}
+
+// scores are stored in the transposition table relative to the node, so a win found at one ply is reused correctly at another
+pub fn score_to_tt(score: Score, ply: usize) -> Score {
+    if is_win(score) {
+        score + ply as Score
+    } else if is_loss(score) {
+        score - ply as Score
+    } else {
+        score
+    }
+}
+
+pub fn score_from_tt(score: Score, ply: usize) -> Score {
+    if is_win(score) {
+        score - ply as Score
+    } else if is_loss(score) {
+        score + ply as Score
+    } else {
+        score
+    }
+}
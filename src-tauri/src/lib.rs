@@ -4,3 +4,6 @@ pub mod solver;
 pub mod simulator;
 pub mod command;
 pub mod evaluation;
+pub mod selfplay;
+pub mod tablebase;
+pub mod book;
@@ -0,0 +1,195 @@
+use std::time::{Duration, Instant};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use crate::board::action::Action;
+use crate::board::board::Board;
+use crate::constants::{BLUE, RED};
+use crate::evaluation::manhattan::ManhattanEvaluation;
+use crate::solver::solver::Solver;
+
+pub struct GameRecord {
+    pub actions: Vec<Action>,
+    pub winner: Option<usize>,
+    // true if the game hit `max_plies` and the winner was decided by remaining distance
+    pub adjudicated: bool,
+    pub red_time: Duration,
+    pub blue_time: Duration,
+    pub red_moves: usize,
+    pub blue_moves: usize,
+}
+
+#[derive(Default, Debug)]
+pub struct MatchResult {
+    // counted from the first solver's point of view
+    pub wins: usize,
+    pub losses: usize,
+    pub draws: usize,
+    pub adjudicated: usize,
+    pub first_time: Duration,
+    pub second_time: Duration,
+    pub first_moves: usize,
+    pub second_moves: usize,
+}
+
+impl MatchResult {
+    pub fn score(&self) -> f64 {
+        let total = self.wins + self.losses + self.draws;
+        if total == 0 {
+            return 0.5;
+        }
+        (self.wins as f64 + 0.5 * self.draws as f64) / total as f64
+    }
+}
+
+pub struct Arena {
+    pub size: usize,
+    // random forward moves played before the solvers take over, so games between deterministic solvers differ
+    pub opening_plies: usize,
+    pub max_plies: usize,
+    pub seed: u64,
+    // print every game's result as `play_match` goes
+    pub verbose: bool,
+}
+
+impl Default for Arena {
+    fn default() -> Self {
+        Arena {
+            size: 9,
+            opening_plies: 4,
+            max_plies: 300,
+            seed: 0,
+            verbose: false,
+        }
+    }
+}
+
+impl Arena {
+    // the random moves leading from `Board::new` to opening `index`
+    pub fn opening_actions(&self, index: u64) -> Vec<Action> {
+        let mut rng = StdRng::seed_from_u64(self.seed.wrapping_add(index));
+        let mut board = Board::new(self.size);
+        let mut next_player = RED;
+        let mut result = Vec::new();
+        for _ in 0..self.opening_plies {
+            let mut actions = board.generate_actions_forward_only(next_player);
+            if actions.is_empty() {
+                break;
+            }
+            // sort first, the move generator's order is not stable between runs
+            actions.sort_by_key(|a| (a.from_x, a.from_y, a.to_x, a.to_y));
+            let action = actions[rng.gen_range(0..actions.len())];
+            board.perform_action(action);
+            result.push(action);
+            next_player = 3 - next_player;
+        }
+        result
+    }
+
+    pub fn opening(&self, index: u64) -> (Board, usize) {
+        let mut board = Board::new(self.size);
+        let mut next_player = RED;
+        for action in self.opening_actions(index) {
+            board.perform_action(action);
+            next_player = 3 - next_player;
+        }
+        (board, next_player)
+    }
+
+    pub fn play_game(&self, red: &dyn Solver, blue: &dyn Solver, opening: u64) -> GameRecord {
+        let (board, next_player) = self.opening(opening);
+        self.play_from(red, blue, board, next_player)
+    }
+
+    // plays on from any position, the record only holds the moves made here
+    pub fn play_from(&self, red: &dyn Solver, blue: &dyn Solver, mut board: Board, mut next_player: usize) -> GameRecord {
+        let mut record = GameRecord {
+            actions: Vec::new(),
+            winner: None,
+            adjudicated: false,
+            red_time: Duration::ZERO,
+            blue_time: Duration::ZERO,
+            red_moves: 0,
+            blue_moves: 0,
+        };
+
+        while board.is_game_over().is_none() && record.actions.len() < self.max_plies {
+            let start = Instant::now();
+            let result = if next_player == RED {
+                red.solve(&board, next_player)
+            } else {
+                blue.solve(&board, next_player)
+            };
+            if next_player == RED {
+                record.red_time += start.elapsed();
+                record.red_moves += 1;
+            } else {
+                record.blue_time += start.elapsed();
+                record.blue_moves += 1;
+            }
+
+            let action = match result {
+                Ok(x) => x.best_move,
+                Err(_) => break,
+            };
+            board.perform_action(action);
+            record.actions.push(action);
+            next_player = 3 - next_player;
+        }
+
+        record.winner = board.is_game_over();
+        if record.winner.is_none() {
+            record.adjudicated = true;
+            let eval = ManhattanEvaluation;
+            let red_left = eval.dis_red(&board);
+            let blue_left = eval.dis_blue(&board);
+            if red_left < blue_left {
+                record.winner = Some(RED);
+            } else if blue_left < red_left {
+                record.winner = Some(BLUE);
+            }
+        }
+
+        record
+    }
+
+    // every opening is played twice with colors swapped
+    pub fn play_match(&self, first: &dyn Solver, second: &dyn Solver, openings: usize) -> MatchResult {
+        let mut result = MatchResult::default();
+
+        for opening in 0..openings as u64 {
+            for first_color in [RED, BLUE] {
+                let record = if first_color == RED {
+                    self.play_game(first, second, opening)
+                } else {
+                    self.play_game(second, first, opening)
+                };
+
+                if first_color == RED {
+                    result.first_time += record.red_time;
+                    result.second_time += record.blue_time;
+                    result.first_moves += record.red_moves;
+                    result.second_moves += record.blue_moves;
+                } else {
+                    result.first_time += record.blue_time;
+                    result.second_time += record.red_time;
+                    result.first_moves += record.blue_moves;
+                    result.second_moves += record.red_moves;
+                }
+
+                if record.adjudicated {
+                    result.adjudicated += 1;
+                }
+                match record.winner {
+                    Some(x) if x == first_color => result.wins += 1,
+                    Some(_) => result.losses += 1,
+                    None => result.draws += 1,
+                }
+                if self.verbose {
+                    println!("opening {} first as {}: {:?} after {} plies", opening, if first_color == RED { "red" } else { "blue" }, record.winner, record.actions.len());
+                }
+            }
+        }
+
+        result
+    }
+}
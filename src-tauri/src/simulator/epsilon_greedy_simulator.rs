@@ -0,0 +1,35 @@
+use rand::Rng;
+use crate::board::action::Action;
+use crate::board::board::Board;
+use crate::simulator::simulator::{playout, PlayoutCutoff, Simulator};
+
+// the forward move with the most progress, ties broken at random, and a random move with probability `epsilon`
+pub struct EpsilonGreedySimulator {
+    pub epsilon: f64,
+    pub cutoff: PlayoutCutoff,
+}
+
+impl Default for EpsilonGreedySimulator {
+    fn default() -> Self {
+        EpsilonGreedySimulator {
+            epsilon: 0.2,
+            cutoff: Default::default(),
+        }
+    }
+}
+
+impl Simulator for EpsilonGreedySimulator {
+    fn simulate(&self, board: &Board, next_player: usize, played: &mut Vec<Action>) -> f64 {
+        let mut rng = rand::thread_rng();
+        let mut best = Vec::new();
+        playout(board, next_player, played, &self.cutoff, |_, player, actions| {
+            if rng.gen::<f64>() < self.epsilon {
+                return rng.gen_range(0..actions.len());
+            }
+            let max_gain = actions.iter().map(|x| x.gain(player)).max().unwrap();
+            best.clear();
+            best.extend((0..actions.len()).filter(|&x| actions[x].gain(player) == max_gain));
+            best[rng.gen_range(0..best.len())]
+        })
+    }
+}
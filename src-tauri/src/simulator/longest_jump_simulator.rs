@@ -0,0 +1,28 @@
+use rand::Rng;
+use crate::board::action::Action;
+use crate::board::board::Board;
+use crate::simulator::simulator::{playout, PlayoutCutoff, Simulator};
+
+// the forward move covering the most distance, ties broken at random
+// unlike progress this also favours long sideways chains, which open lanes for later jumps
+#[derive(Default)]
+pub struct LongestJumpSimulator {
+    pub cutoff: PlayoutCutoff,
+}
+
+fn length(action: &Action) -> i32 {
+    (action.to_x as i32 - action.from_x as i32).abs() + (action.to_y as i32 - action.from_y as i32).abs()
+}
+
+impl Simulator for LongestJumpSimulator {
+    fn simulate(&self, board: &Board, next_player: usize, played: &mut Vec<Action>) -> f64 {
+        let mut rng = rand::thread_rng();
+        let mut best = Vec::new();
+        playout(board, next_player, played, &self.cutoff, |_, _, actions| {
+            let max_length = actions.iter().map(length).max().unwrap();
+            best.clear();
+            best.extend((0..actions.len()).filter(|&x| length(&actions[x]) == max_length));
+            best[rng.gen_range(0..best.len())]
+        })
+    }
+}
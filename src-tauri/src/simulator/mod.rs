@@ -1,2 +1,5 @@
 pub mod simulator;
 pub mod naive_simulator;
+pub mod epsilon_greedy_simulator;
+pub mod longest_jump_simulator;
+pub mod softmax_simulator;
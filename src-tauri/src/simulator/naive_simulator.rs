@@ -1,39 +1,25 @@
+use crate::board::action::Action;
 use crate::board::board::Board;
 use rand::Rng;
-use crate::constants::RED;
+use crate::simulator::simulator::{playout, PlayoutCutoff, Simulator};
 
-pub struct NaiveSimulator;
-
-impl Default for NaiveSimulator {
-    fn default() -> Self {
-        NaiveSimulator
-    }
+// uniformly random forward moves
+#[derive(Default)]
+pub struct NaiveSimulator {
+    pub cutoff: PlayoutCutoff,
 }
 
 impl NaiveSimulator {
     // true if red wins, false if blue wins
-    pub fn simulate(&self, board: &Board, next_player: usize) -> bool {
-        // println!("simulate start");
-        let mut b = board.clone();
-        let mut next_player = next_player;
-        let mut game_over = board.is_game_over();
-
-        while game_over.is_none() {
-            let mut actions = b.generate_actions_forward_only(next_player);
-            if actions.is_empty() {
-                // actions = b.generate_actions_all(next_player);
-                return next_player == RED;
-            }
-
-            let random_index = rand::thread_rng().gen::<usize>() % actions.len();
-
-            let action = actions[random_index];
-            b.perform_action(action);
-            // println!("{}", b);
-            next_player = 3 - next_player;
-            game_over = b.is_game_over();
-        }
+    pub fn simulate_once(&self, board: &Board, next_player: usize) -> bool {
+        self.simulate(board, next_player, &mut Vec::new()) > 0.5
+    }
+}
 
-        game_over.unwrap() == RED
+impl Simulator for NaiveSimulator {
+    fn simulate(&self, board: &Board, next_player: usize, played: &mut Vec<Action>) -> f64 {
+        playout(board, next_player, played, &self.cutoff, |_, _, actions| {
+            rand::thread_rng().gen::<usize>() % actions.len()
+        })
     }
 }
@@ -0,0 +1,111 @@
+use std::sync::Arc;
+use crate::board::action::Action;
+use crate::board::board::Board;
+use crate::constants::RED;
+use crate::evaluation::evaluation::Evaluation;
+use crate::simulator::epsilon_greedy_simulator::EpsilonGreedySimulator;
+use crate::simulator::longest_jump_simulator::LongestJumpSimulator;
+use crate::simulator::naive_simulator::NaiveSimulator;
+use crate::simulator::softmax_simulator::SoftmaxSimulator;
+
+// plays a game out from a position for MCTS
+pub trait Simulator: Send + Sync {
+    // red's reward in [0, 1], the moves played are appended to `played`, alternating from `next_player`
+    fn simulate(&self, board: &Board, next_player: usize, played: &mut Vec<Action>) -> f64;
+}
+
+impl<S: Simulator + ?Sized> Simulator for Box<S> {
+    fn simulate(&self, board: &Board, next_player: usize, played: &mut Vec<Action>) -> f64 {
+        (**self).simulate(board, next_player, played)
+    }
+}
+
+// stops playouts after `max_plies` and scores the position with `evaluation` instead of playing to the end
+#[derive(Clone)]
+pub struct PlayoutCutoff {
+    pub max_plies: Option<usize>,
+    pub evaluation: Option<Arc<dyn Evaluation>>,
+    // evaluation units per e-fold of the odds, see `win_probability`
+    pub scale: f64,
+}
+
+impl Default for PlayoutCutoff {
+    fn default() -> Self {
+        PlayoutCutoff {
+            max_plies: None,
+            evaluation: None,
+            scale: 8.0,
+        }
+    }
+}
+
+impl PlayoutCutoff {
+    pub fn new(max_plies: usize, evaluation: Arc<dyn Evaluation>, scale: f64) -> Self {
+        PlayoutCutoff {
+            max_plies: Some(max_plies),
+            evaluation: Some(evaluation),
+            scale,
+        }
+    }
+}
+
+// logistic mapping of an evaluation for a player to that player's chance of winning
+pub fn win_probability(score: f64, scale: f64) -> f64 {
+    1.0 / (1.0 + (-score / scale).exp())
+}
+
+// the playout loop every simulator shares, `choose` picks one of the side to move's forward moves
+// a side without forward moves counts as the winner
+pub fn playout(board: &Board, next_player: usize, played: &mut Vec<Action>, cutoff: &PlayoutCutoff, mut choose: impl FnMut(&Board, usize, &[Action]) -> usize) -> f64 {
+    let mut b = board.clone();
+    let mut next_player = next_player;
+    let mut game_over = board.is_game_over();
+    let mut plies = 0;
+
+    while game_over.is_none() {
+        if let (Some(max_plies), Some(evaluation)) = (cutoff.max_plies, &cutoff.evaluation) {
+            if plies >= max_plies {
+                return win_probability(evaluation.evaluate(&b, RED) as f64, cutoff.scale);
+            }
+        }
+
+        let actions = b.generate_actions_forward_only(next_player);
+        if actions.is_empty() {
+            return if next_player == RED { 1.0 } else { 0.0 };
+        }
+
+        let action = actions[choose(&b, next_player, &actions)];
+        b.perform_action(action);
+        played.push(action);
+        plies += 1;
+        next_player = 3 - next_player;
+        game_over = b.is_game_over();
+    }
+
+    if game_over.unwrap() == RED { 1.0 } else { 0.0 }
+}
+
+// the playout policies MCTS can be configured with
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum PlayoutPolicy {
+    // uniformly random forward moves
+    Uniform,
+    // the move with the most progress, a random one with probability `epsilon`
+    EpsilonGreedy(f64),
+    // the longest forward jump
+    LongestJump,
+    // sampled from a softmax over evaluation deltas at this temperature
+    Softmax(f64),
+}
+
+impl PlayoutPolicy {
+    // `evaluation` scores the softmax policy's moves
+    pub fn simulator(&self, cutoff: PlayoutCutoff, evaluation: Arc<dyn Evaluation>) -> Box<dyn Simulator> {
+        match *self {
+            PlayoutPolicy::Uniform => Box::new(NaiveSimulator { cutoff }),
+            PlayoutPolicy::EpsilonGreedy(epsilon) => Box::new(EpsilonGreedySimulator { epsilon, cutoff }),
+            PlayoutPolicy::LongestJump => Box::new(LongestJumpSimulator { cutoff }),
+            PlayoutPolicy::Softmax(temperature) => Box::new(SoftmaxSimulator { evaluation, temperature, cutoff }),
+        }
+    }
+}
@@ -0,0 +1,52 @@
+use std::sync::Arc;
+use rand::Rng;
+use crate::board::action::Action;
+use crate::board::board::Board;
+use crate::evaluation::evaluation::Evaluation;
+use crate::evaluation::manhattan::ManhattanEvaluation;
+use crate::simulator::simulator::{playout, PlayoutCutoff, Simulator};
+use crate::solver::mcts_policy::softmax;
+
+// samples moves from a softmax over how much each improves the evaluation for the side to move
+pub struct SoftmaxSimulator {
+    pub evaluation: Arc<dyn Evaluation>,
+    // in evaluation units, lower is greedier
+    pub temperature: f64,
+    pub cutoff: PlayoutCutoff,
+}
+
+impl Default for SoftmaxSimulator {
+    fn default() -> Self {
+        SoftmaxSimulator {
+            evaluation: Arc::new(ManhattanEvaluation),
+            temperature: 2.0,
+            cutoff: Default::default(),
+        }
+    }
+}
+
+impl Simulator for SoftmaxSimulator {
+    fn simulate(&self, board: &Board, next_player: usize, played: &mut Vec<Action>) -> f64 {
+        let mut rng = rand::thread_rng();
+        playout(board, next_player, played, &self.cutoff, |b, player, actions| {
+            let mut b = b.clone();
+            let scores: Vec<f64> = actions.iter().map(|&action| {
+                b.perform_action(action);
+                let after = -self.evaluation.evaluate(&b, 3 - player) as f64;
+                b.undo_action(action);
+                after
+            }).collect();
+
+            // the evaluation before the move is the same for every move, so it cancels in the softmax
+            let weights = softmax(&scores, self.temperature);
+            let mut x = rng.gen::<f64>();
+            for (index, weight) in weights.iter().enumerate() {
+                if x < *weight {
+                    return index;
+                }
+                x -= weight;
+            }
+            actions.len() - 1
+        })
+    }
+}
@@ -1,12 +1,173 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::thread;
+use std::time::Instant;
 use crate::board::action::Action;
 use crate::board::board::Board;
+use crate::board::zobrist;
 use crate::evaluation::evaluation::Evaluation;
 use crate::evaluation::manhattan::ManhattanEvaluation;
-use crate::solver::solver::Solver;
+use crate::evaluation::score::{is_decisive, is_loss, is_win, loss_in, moves_to_end, score_from_tt, score_to_tt, win_in, Score, INFINITE_SCORE};
+use crate::solver::search_limits::SearchLimits;
+use crate::solver::solver::{ProvenOutcome, SearchResult, Solver, SolverError};
+use crate::solver::transposition_table::{Bound, TTEntry, TranspositionTable};
+use crate::tablebase::tablebase::Tablebase;
+
+// width of the zero window used to test whether a move beats alpha
+// scores are integral, so a window of 1 cannot skip a better move
+const NULL_WINDOW: Score = 1;
+
+// how retreating moves are treated once the search is `retreat_pruning_ply` plies deep
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RetreatPruning {
+    Off,
+    // retreats are searched after every forward move and one ply shallower
+    Delay,
+    // retreats are not searched unless the side has no forward move at all
+    Prune,
+}
+
+#[derive(Clone)]
+pub struct AlphaBetaSolverConfig {
+    // half width of the window placed around the previous iteration's score
+    // 0 disables aspiration windows
+    pub aspiration_window: Score,
+
+    // at the horizon keep searching jumps that gain at least this much
+    pub jump_extension_gain: i32,
+    // maximum number of extra plies searched past the horizon, 0 disables the extension
+    pub jump_extension_limit: usize,
+
+    pub retreat_pruning: RetreatPruning,
+    pub retreat_pruning_ply: usize,
+
+    // moves after the first `lmr_min_index` ones are searched one ply shallower, and re-searched if they beat alpha
+    pub late_move_reduction: bool,
+    pub lmr_min_depth: usize,
+    pub lmr_min_index: usize,
+
+    // near the leaves, skip small moves when the static evaluation plus a margin cannot reach alpha
+    // `futility_margins[d - 1]` is the margin used at remaining depth d
+    pub futility_pruning: bool,
+    pub futility_margins: Vec<Score>,
+
+    // the transposition table has 2^tt_size_log2 entries of 16 bytes
+    pub tt_size_log2: usize,
+    // lazy SMP, helper threads search the same position and share the transposition table
+    // with a single thread the search is fully deterministic
+    pub threads: usize,
+}
+
+impl Default for AlphaBetaSolverConfig {
+    fn default() -> Self {
+        AlphaBetaSolverConfig {
+            aspiration_window: 4,
+            jump_extension_gain: 4,
+            jump_extension_limit: 4,
+            retreat_pruning: RetreatPruning::Delay,
+            retreat_pruning_ply: 2,
+            late_move_reduction: true,
+            lmr_min_depth: 3,
+            lmr_min_index: 4,
+            futility_pruning: true,
+            futility_margins: vec![6, 12],
+            tt_size_log2: 20,
+            threads: 1,
+        }
+    }
+}
+
+// nodes a thread searches between looks at the clock and the shared node count
+// a node limit can be overshot by up to this many nodes per thread
+const NODE_CHECK_INTERVAL: u64 = 1024;
+
+// per-thread search state
+pub struct SearchContext<'a> {
+    // the position being searched and the side to move in it, updated by `make` and `unmake`
+    pub board: Board,
+    pub next_player: usize,
+    pub tt: &'a TranspositionTable,
+    pub limits: &'a SearchLimits,
+    // set by the main thread to stop the helpers
+    pub stop: &'a AtomicBool,
+    // nodes of every thread of the search, checked against `limits.max_nodes`
+    pub total_nodes: &'a AtomicU64,
+    // nodes of this thread
+    pub nodes: u64,
+    // part of `nodes` already added to `total_nodes`
+    counted_nodes: u64,
+    // triangular principal variation table, pv[ply] is the best line found from that ply
+    pub pv: Vec<Vec<Action>>,
+    aborted: bool,
+}
+
+impl<'a> SearchContext<'a> {
+    pub fn new(board: &Board, next_player: usize, tt: &'a TranspositionTable, limits: &'a SearchLimits, stop: &'a AtomicBool, total_nodes: &'a AtomicU64) -> Self {
+        Self {
+            board: board.clone(),
+            next_player,
+            tt,
+            limits,
+            stop,
+            total_nodes,
+            nodes: 0,
+            counted_nodes: 0,
+            pv: Vec::new(),
+            aborted: false,
+        }
+    }
+
+    fn make(&mut self, action: Action) {
+        self.board.perform_action(action);
+        self.next_player = 3 - self.next_player;
+    }
+
+    fn unmake(&mut self, action: Action) {
+        self.board.undo_action(action);
+        self.next_player = 3 - self.next_player;
+    }
+
+    fn clear_pv(&mut self, ply: usize) {
+        if self.pv.len() < ply + 2 {
+            self.pv.resize(ply + 2, Vec::new());
+        }
+        self.pv[ply].clear();
+    }
+
+    // pv[ply] becomes `action` followed by the line of the child just searched
+    fn update_pv(&mut self, ply: usize, action: Action) {
+        let (head, tail) = self.pv.split_at_mut(ply + 1);
+        head[ply].clear();
+        head[ply].push(action);
+        head[ply].extend_from_slice(&tail[0]);
+    }
+
+    // once true it stays true, so every level of the search unwinds
+    pub fn stopped(&mut self) -> bool {
+        if self.aborted {
+            return true;
+        }
+        if self.stop.load(Ordering::Relaxed) {
+            self.aborted = true;
+        } else if self.nodes - self.counted_nodes >= NODE_CHECK_INTERVAL {
+            let new_nodes = self.nodes - self.counted_nodes;
+            self.counted_nodes = self.nodes;
+            let total = self.total_nodes.fetch_add(new_nodes, Ordering::Relaxed) + new_nodes;
+            self.aborted = self.limits.should_stop(total);
+        }
+        self.aborted
+    }
+}
 
 pub struct AlphaBetaSolver {
     pub evaluator: Box<dyn Evaluation>,
     pub max_depth: usize,
+    pub config: AlphaBetaSolverConfig,
+    // when set every search reuses this table instead of allocating a fresh one,
+    // results then depend on earlier searches
+    pub tt: Option<Arc<TranspositionTable>>,
+    // exact values for small boards, probed instead of searching covered positions
+    pub tablebase: Option<Arc<Tablebase>>,
 }
 
 impl Default for AlphaBetaSolver {
@@ -14,54 +175,350 @@ impl Default for AlphaBetaSolver {
         AlphaBetaSolver {
             evaluator: Box::new(ManhattanEvaluation),
             max_depth: 6,
+            config: Default::default(),
+            tt: None,
+            tablebase: None,
         }
     }
 }
 
 impl AlphaBetaSolver {
     pub fn new(eval: Box<dyn Evaluation>, max_depth: usize) -> Self {
+        Self::with_config(eval, max_depth, Default::default())
+    }
+
+    pub fn with_config(eval: Box<dyn Evaluation>, max_depth: usize, config: AlphaBetaSolverConfig) -> Self {
         Self {
             evaluator: eval,
             max_depth,
+            config,
+            tt: None,
+            tablebase: None,
+        }
+    }
+
+    // big forward jumps first, then a fixed coordinate order so that equal moves are always tried the same way
+    // `hint` (usually the best move of the previous iteration) is moved to the front
+    pub fn order_actions(&self, actions: &mut [Action], player: usize, hint: Action) {
+        actions.sort_by_key(|a| (-a.gain(player), a.from_x, a.from_y, a.to_x, a.to_y));
+        if let Some(index) = actions.iter().position(|&a| a == hint) {
+            actions[..=index].rotate_right(1);
+        }
+    }
+
+    // actions searched at `ply`, retreats are dropped here when pruning is enabled and forward moves exist
+    pub fn generate_actions(&self, board: &Board, next_player: usize, ply: usize) -> Vec<Action> {
+        if self.config.retreat_pruning == RetreatPruning::Prune && ply >= self.config.retreat_pruning_ply {
+            let actions = board.generate_actions_forward_only(next_player);
+            if !actions.is_empty() {
+                return actions;
+            }
         }
+        board.generate_actions_all(next_player)
     }
 
-    pub fn ab_search(&self, board: &mut Board, next_player: usize, depth: usize, alpha: f64, beta: f64, best_move: &mut Action) -> f64 {
+    // fail-soft principal variation search
+    // `best_move` is read as an ordering hint and overwritten with the best move found
+    // the result of a stopped search is meaningless and must be discarded by the caller,
+    // iterative deepening keeps the move of the last completed iteration
+    pub fn ab_search(&self, ctx: &mut SearchContext, depth: usize, ply: usize, alpha: Score, beta: Score, best_move: &mut Action) -> Score {
+        let next_player = ctx.next_player;
+        ctx.nodes += 1;
+        ctx.clear_pv(ply);
+        if ctx.stopped() {
+            return 0;
+        }
+        if let Some(winner) = ctx.board.is_game_over() {
+            return if winner == next_player { win_in(ply) } else { loss_in(ply) };
+        }
+        if ply > 0 {
+            if let Some(value) = self.tablebase.as_ref().and_then(|x| x.probe(&ctx.board, next_player)) {
+                return value.score(ply);
+            }
+        }
         if depth == 0 {
-            let v = self.evaluator.evaluate(board, next_player);
-            // println!("{}", v);
-            return v;
+            return self.jump_search(ctx, self.config.jump_extension_limit, ply, alpha, beta);
         }
 
+        let key = zobrist::hash(&ctx.board, next_player);
+        let mut hint = *best_move;
+        if let Some(entry) = ctx.tt.probe(key) {
+            // the root always searches, its caller needs a move
+            if ply > 0 && entry.depth >= depth {
+                let score = score_from_tt(entry.score, ply);
+                let cutoff = match entry.bound {
+                    Bound::Exact => true,
+                    Bound::Lower => score >= beta,
+                    Bound::Upper => score <= alpha,
+                };
+                if cutoff {
+                    if let Some(action) = entry.best_move {
+                        *best_move = action;
+                    }
+                    return score;
+                }
+            }
+            if ply > 0 {
+                if let Some(action) = entry.best_move {
+                    hint = action;
+                }
+            }
+        }
+
+        let original_alpha = alpha;
         let mut alpha = alpha;
+        let mut best_value = -INFINITE_SCORE;
+        let mut best_action = None;
 
-        let actions = board.generate_actions_all(next_player);
-        for &action in actions.iter() {
-            board.perform_action(action);
+        let delay_retreats = self.config.retreat_pruning == RetreatPruning::Delay && ply >= self.config.retreat_pruning_ply;
+        let mut actions = self.generate_actions(&ctx.board, next_player, ply);
+        self.order_actions(&mut actions, next_player, hint);
+        if delay_retreats {
+            actions.sort_by_key(|a| !a.is_forward(next_player));
+        }
+
+        let futility_value = if self.config.futility_pruning && depth <= self.config.futility_margins.len() {
+            Some(self.evaluator.evaluate(&ctx.board, next_player) + self.config.futility_margins[depth - 1])
+        } else {
+            None
+        };
+
+        let new_depth = depth - 1;
+        for (index, &action) in actions.iter().enumerate() {
+            // jumps big enough for the horizon extension are never pruned or reduced
+            let tactical = action.gain(next_player) >= self.config.jump_extension_gain;
+
+            if let Some(futility_value) = futility_value {
+                if index > 0 && !tactical && futility_value <= alpha {
+                    if futility_value > best_value {
+                        best_value = futility_value;
+                    }
+                    continue;
+                }
+            }
+
+            let mut reduction = 0;
+            if delay_retreats && !action.is_forward(next_player) {
+                reduction += 1;
+            }
+            if self.config.late_move_reduction && depth >= self.config.lmr_min_depth && index >= self.config.lmr_min_index && !tactical {
+                reduction += 1;
+            }
+            let reduction = reduction.min(new_depth);
+
+            ctx.make(action);
             let mut ph = Action::from_usize(0, 0, 0, 0);
-            let value = -self.ab_search(board, 3 - next_player, depth - 1, -beta, -alpha, &mut ph);
-            board.undo_action(action);
-            if value >= beta {
+            let value = if index == 0 {
+                -self.ab_search(ctx, new_depth, ply + 1, -beta, -alpha, &mut ph)
+            } else {
+                let mut value = -self.ab_search(ctx, new_depth - reduction, ply + 1, -alpha - NULL_WINDOW, -alpha, &mut ph);
+                if value > alpha && reduction > 0 {
+                    value = -self.ab_search(ctx, new_depth, ply + 1, -alpha - NULL_WINDOW, -alpha, &mut ph);
+                }
+                if value > alpha && value < beta {
+                    // the zero window only proved the move is better, get its real value
+                    value = -self.ab_search(ctx, new_depth, ply + 1, -beta, -alpha, &mut ph);
+                }
+                value
+            };
+            ctx.unmake(action);
+
+            if ctx.stopped() {
+                return 0;
+            }
+
+            if value > best_value || best_action.is_none() {
+                best_value = value;
+                best_action = Some(action);
                 *best_move = action;
-                return beta;
+                if value > alpha {
+                    ctx.update_pv(ply, action);
+                }
+            }
+            if value >= beta {
+                break;
+            }
+            if value > alpha {
+                alpha = value;
+            }
+        }
+
+        let bound = if best_value >= beta {
+            Bound::Lower
+        } else if best_value <= original_alpha {
+            Bound::Upper
+        } else {
+            Bound::Exact
+        };
+        ctx.tt.store(key, TTEntry {
+            best_move: best_action,
+            depth,
+            bound,
+            score: score_to_tt(best_value, ply),
+        });
+
+        best_value
+    }
+
+    // quiescence-style extension, only large forward jumps are searched until the position is quiet
+    // the side to move may always decline them, so the static evaluation is a lower bound
+    pub fn jump_search(&self, ctx: &mut SearchContext, extension: usize, ply: usize, alpha: Score, beta: Score) -> Score {
+        let next_player = ctx.next_player;
+        ctx.nodes += 1;
+        if let Some(winner) = ctx.board.is_game_over() {
+            return if winner == next_player { win_in(ply) } else { loss_in(ply) };
+        }
+        let stand_pat = self.evaluator.evaluate(&ctx.board, next_player);
+        if extension == 0 || stand_pat >= beta {
+            return stand_pat;
+        }
+
+        let mut alpha = alpha.max(stand_pat);
+        let mut best_value = stand_pat;
+
+        let gain = self.config.jump_extension_gain;
+        let mut actions = ctx.board.generate_actions_all(next_player);
+        actions.retain(|a| a.gain(next_player) >= gain);
+        self.order_actions(&mut actions, next_player, Action::from_usize(0, 0, 0, 0));
+        for &action in actions.iter() {
+            ctx.make(action);
+            let value = -self.jump_search(ctx, extension - 1, ply + 1, -beta, -alpha);
+            ctx.unmake(action);
+
+            if value > best_value {
+                best_value = value;
+            }
+            if value >= beta {
+                return value;
             }
             if value > alpha {
-                *best_move = action;
                 alpha = value;
             }
         }
 
-        alpha
+        best_value
+    }
+
+    // root search with an aspiration window around `previous`, widened on every fail until the score fits
+    pub fn aspiration_search(&self, ctx: &mut SearchContext, depth: usize, previous: Option<Score>, best_move: &mut Action) -> Score {
+        let mut delta = self.config.aspiration_window;
+        let (mut alpha, mut beta) = match previous {
+            Some(score) if !is_decisive(score) && delta > 0 => (score - delta, score + delta),
+            _ => (-INFINITE_SCORE, INFINITE_SCORE),
+        };
+
+        loop {
+            let mut action = *best_move;
+            let value = self.ab_search(ctx, depth, 0, alpha, beta, &mut action);
+            if ctx.stopped() {
+                return 0;
+            }
+            if value <= alpha && alpha > -INFINITE_SCORE {
+                delta *= 2;
+                alpha = (value - delta).max(-INFINITE_SCORE);
+            } else if value >= beta && beta < INFINITE_SCORE {
+                // a fail high still leaves us with a move that is better than expected
+                *best_move = action;
+                delta *= 2;
+                beta = (value + delta).min(INFINITE_SCORE);
+            } else {
+                *best_move = action;
+                return value;
+            }
+        }
+    }
+
+    // iterative deepening from `start_depth`, `result` keeps the last completed iteration
+    // `result.best_move` is also the ordering hint for the first iteration
+    pub fn iterative_deepening(&self, ctx: &mut SearchContext, start_depth: usize, result: &mut SearchResult) {
+        let mut previous = None;
+        for depth in start_depth..=ctx.limits.depth(self.max_depth) {
+            let mut action = result.best_move;
+            let value = self.aspiration_search(ctx, depth, previous, &mut action);
+            if ctx.stopped() {
+                break;
+            }
+
+            previous = Some(value);
+            result.best_move = action;
+            result.score = value as f64;
+            result.depth = depth;
+            result.pv = match ctx.pv.first() {
+                Some(pv) if pv.first() == Some(&action) => pv.clone(),
+                _ => vec![action],
+            };
+            // with retreats pruned a decisive score only holds over forward moves, which proves nothing
+            if self.config.retreat_pruning == RetreatPruning::Prune {
+                result.moves_to_end = None;
+                result.proven = None;
+            } else {
+                result.moves_to_end = moves_to_end(value);
+                result.proven = if is_win(value) {
+                    Some(ProvenOutcome::Win)
+                } else if is_loss(value) {
+                    Some(ProvenOutcome::Loss)
+                } else {
+                    None
+                };
+            }
+
+            if is_decisive(value) {
+                break;
+            }
+        }
     }
 }
 
 impl Solver for AlphaBetaSolver {
-    fn solve(&self, board: &Board, next_player: usize) -> Action {
-        let all_actions = board.generate_actions_all(next_player);
-        let mut action = all_actions[0];
-        let mut b = board.clone();
+    fn solve_with_limits(&self, board: &Board, next_player: usize, limits: &SearchLimits) -> Result<SearchResult, SolverError> {
+        let start = Instant::now();
+        let mut all_actions = board.generate_actions_all(next_player);
+        if all_actions.is_empty() {
+            return Err(SolverError::NoLegalMoves);
+        }
+        if let Some(result) = self.tablebase.as_ref().and_then(|x| x.solve(board, next_player)) {
+            return Ok(result);
+        }
+        self.order_actions(&mut all_actions, next_player, Action::from_usize(0, 0, 0, 0));
+        let mut result = SearchResult::new(all_actions[0]);
+
+        let local_tt;
+        let tt = match &self.tt {
+            Some(x) => x.as_ref(),
+            None => {
+                local_tt = TranspositionTable::new(self.config.tt_size_log2);
+                &local_tt
+            }
+        };
+        let stop = AtomicBool::new(false);
+        let total_nodes = AtomicU64::new(0);
+
+        thread::scope(|scope| {
+            let mut helpers = Vec::new();
+            for index in 1..self.config.threads {
+                let stop = &stop;
+                let total_nodes = &total_nodes;
+                let mut helper_result = result.clone();
+                helpers.push(scope.spawn(move || {
+                    let mut ctx = SearchContext::new(board, next_player, tt, limits, stop, total_nodes);
+                    // odd helpers skip the first iteration so that threads spread over different depths
+                    self.iterative_deepening(&mut ctx, 1 + index % 2, &mut helper_result);
+                    ctx.nodes
+                }));
+            }
+
+            let main_stop = AtomicBool::new(false);
+            let mut ctx = SearchContext::new(board, next_player, tt, limits, &main_stop, &total_nodes);
+            self.iterative_deepening(&mut ctx, 1, &mut result);
+            stop.store(true, Ordering::Relaxed);
+
+            result.nodes = ctx.nodes;
+            for helper in helpers {
+                result.nodes += helper.join().unwrap();
+            }
+        });
 
-        self.ab_search(&mut b, next_player, self.max_depth, -f64::INFINITY, f64::INFINITY, &mut action);
-        action
+        result.elapsed = start.elapsed();
+        Ok(result)
     }
 }
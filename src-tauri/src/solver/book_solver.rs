@@ -0,0 +1,54 @@
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use crate::board::board::Board;
+use crate::book::book::OpeningBook;
+use crate::solver::search_limits::SearchLimits;
+use crate::solver::solver::{SearchResult, Solver, SolverError};
+
+// plays from an opening book while the position is in it, otherwise asks `inner`
+pub struct BookSolver {
+    pub book: Arc<OpeningBook>,
+    pub inner: Box<dyn Solver + Send + Sync>,
+    // when set book moves are drawn by weight instead of always taking the heaviest
+    pub rng: Option<Mutex<StdRng>>,
+}
+
+impl BookSolver {
+    pub fn new(book: Arc<OpeningBook>, inner: Box<dyn Solver + Send + Sync>) -> BookSolver {
+        BookSolver {
+            book,
+            inner,
+            rng: None,
+        }
+    }
+
+    pub fn weighted(book: Arc<OpeningBook>, inner: Box<dyn Solver + Send + Sync>, seed: u64) -> BookSolver {
+        BookSolver {
+            book,
+            inner,
+            rng: Some(Mutex::new(StdRng::seed_from_u64(seed))),
+        }
+    }
+}
+
+impl Solver for BookSolver {
+    fn solve_with_limits(&self, board: &Board, next_player: usize, limits: &SearchLimits) -> Result<SearchResult, SolverError> {
+        let start = Instant::now();
+        let action = match &self.rng {
+            Some(rng) => self.book.pick(board, next_player, &mut *rng.lock().unwrap()),
+            None => self.book.best(board, next_player),
+        };
+
+        // a hash collision could hand back a move that does not exist here
+        match action {
+            Some(action) if board.generate_actions_all(next_player).contains(&action) => {
+                let mut result = SearchResult::new(action);
+                result.elapsed = start.elapsed();
+                Ok(result)
+            },
+            _ => self.inner.solve_with_limits(board, next_player, limits),
+        }
+    }
+}
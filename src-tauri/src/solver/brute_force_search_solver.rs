@@ -1,11 +1,13 @@
 use std::collections::hash_map::DefaultHasher;
 use std::collections::{HashSet, VecDeque};
 use std::hash::Hasher;
+use std::time::Instant;
 use smallvec::SmallVec;
 use crate::board::action::Action;
 use crate::board::board::Board;
-use crate::constants::{EMPTY, RED, BLUE};
-use crate::solver::solver::{MaybeSolver, Solver};
+use crate::constants::{RED, BLUE};
+use crate::solver::search_limits::SearchLimits;
+use crate::solver::solver::{SearchResult, Solver, SolverError};
 
 pub struct BruteForceSearchSolver {
     pub max_depth: usize,
@@ -43,26 +45,19 @@ impl BruteForceSearchSolver {
     }
 
     pub fn extract_board(&self, board: &Board, player: usize) -> Board {
-        let mut ret = board.clone();
-        let size = board.size;
-        for i in 0..size {
-            for j in 0..size {
-                if ret.data[i][j] != player {
-                    ret.data[i][j] = EMPTY;
-                }
-            }
-        }
-        ret
+        board.keep_player(player)
     }
 
-    pub fn dfs(&self, board: &mut Board, actions: &mut SmallVec<[Action; 32]>, depth: usize, player: usize) -> bool {
+    // `remaining` moves left for `player` to fill its camp
+    pub fn dfs(&self, board: &mut Board, actions: &mut SmallVec<[Action; 32]>, remaining: usize, player: usize, limits: &SearchLimits, nodes: &mut u64) -> bool {
+        *nodes += 1;
         if player == RED && board.is_red_winning() {
             return true;
         } else if player == BLUE && board.is_blue_winning() {
             return true;
         }
 
-        if depth == self.max_depth {
+        if remaining == 0 || limits.should_stop(*nodes) {
             return false;
         }
 
@@ -70,7 +65,7 @@ impl BruteForceSearchSolver {
         for &action in candidate_actions.iter() {
             board.perform_action(action);
             actions.push(action);
-            if self.dfs(board, actions, depth + 1, player) {
+            if self.dfs(board, actions, remaining - 1, player, limits, nodes) {
                 return true;
             }
             board.undo_action(action);
@@ -114,7 +109,7 @@ impl BruteForceSearchSolver {
                 let new_hash = get_board_hash(&new_board);
                 let mut new_actions = p.actions.clone();
                 new_actions.push(action);
-                if !vis.contains(&new_hash) {
+                if vis.insert(new_hash) {
                     queue.push_back(Node {
                         data: new_board,
                         depth: p.depth + 1,
@@ -128,8 +123,10 @@ impl BruteForceSearchSolver {
     }
 }
 
-impl MaybeSolver for BruteForceSearchSolver {
-    fn solve(&self, board: &Board, next_player: usize) -> Option<Action> {
+impl Solver for BruteForceSearchSolver {
+    // only answers when the side to move can fill its camp within `max_depth` moves, `NotFound` otherwise
+    fn solve_with_limits(&self, board: &Board, next_player: usize, limits: &SearchLimits) -> Result<SearchResult, SolverError> {
+        let start = Instant::now();
         let mut b = self.extract_board(board, next_player);
         // let result = self.search(&b, self.max_depth, next_player);
         // match result {
@@ -138,12 +135,19 @@ impl MaybeSolver for BruteForceSearchSolver {
         // }
 
         let mut result_actions: SmallVec<[Action; 32]> = SmallVec::new();
-        let result = self.dfs(&mut b, &mut result_actions, 0, next_player);
-        if result {
+        let mut nodes = 0;
+        let max_depth = limits.depth(self.max_depth);
+        let found = self.dfs(&mut b, &mut result_actions, max_depth, next_player, limits, &mut nodes);
+        if found && !result_actions.is_empty() {
             println!("{:?}", result_actions);
-            Some(result_actions[0])
+            let mut result = SearchResult::new(result_actions[0]);
+            result.depth = result_actions.len();
+            result.nodes = nodes;
+            result.pv = result_actions.to_vec();
+            result.elapsed = start.elapsed();
+            Ok(result)
         } else {
-            None
+            Err(SolverError::NotFound)
         }
     }
 }
@@ -0,0 +1,288 @@
+use std::collections::{HashMap, HashSet};
+use std::time::Instant;
+use crate::board::action::Action;
+use crate::board::board::Board;
+use crate::board::zobrist;
+use crate::constants::RED;
+use crate::solver::search_limits::SearchLimits;
+use crate::solver::solver::{SearchResult, Solver, SolverError};
+
+// single-player IDA* planner, finds the shortest sequence of moves filling a side's camp
+// the opponent's pieces stay where they are, as obstacles and as pivots for jumps
+pub struct CampPlanner {
+    // the planner gives up once it has expanded this many nodes
+    pub max_nodes: u64,
+}
+
+impl Default for CampPlanner {
+    fn default() -> Self {
+        CampPlanner {
+            max_nodes: 200_000,
+        }
+    }
+}
+
+pub struct Plan {
+    pub actions: Vec<Action>,
+    pub nodes: u64,
+}
+
+struct PlanSearch<'a> {
+    player: usize,
+    limits: &'a SearchLimits,
+    max_nodes: u64,
+    nodes: u64,
+    // transposition table, lower bounds on the remaining moves learned from failed iterations
+    lower_bounds: HashMap<u64, usize>,
+    path: Vec<Action>,
+}
+
+// manhattan distance from (x, y) to the nearest cell of `player`'s camp
+fn distance_to_goal(board: &Board, player: usize, x: usize, y: usize) -> usize {
+    if player == RED {
+        (board.size - board.camp).saturating_sub(x) + (board.size - board.camp).saturating_sub(y)
+    } else {
+        (x + 1).saturating_sub(board.camp) + (y + 1).saturating_sub(board.camp)
+    }
+}
+
+impl CampPlanner {
+    pub fn new(max_nodes: u64) -> CampPlanner {
+        CampPlanner {
+            max_nodes,
+        }
+    }
+
+    // admissible lower bound on the moves `player` needs, the largest of
+    // - pieces outside the camp, every move brings in at most one
+    // - summed distance to the camp divided by the longest displacement a single move can have
+    // - parity deficit: jumps keep (x % 2, y % 2), so pieces missing from a parity class of the camp need a step each
+    pub fn lower_bound(&self, board: &Board, player: usize) -> usize {
+        let mut outside = 0;
+        let mut total_distance = 0;
+        let mut pieces = [0_usize; 4];
+        let mut goal_cells = [0_usize; 4];
+
+        for i in 0..board.size {
+            for j in 0..board.size {
+                let class = (i % 2) * 2 + j % 2;
+                if board.is_in_goal(player, i, j) {
+                    goal_cells[class] += 1;
+                }
+                if board.data[i][j] == player {
+                    pieces[class] += 1;
+                    if !board.is_in_goal(player, i, j) {
+                        outside += 1;
+                        total_distance += distance_to_goal(board, player, i, j);
+                    }
+                }
+            }
+        }
+
+        let max_gain = 2 * (board.size - 1);
+        let mut deficit = 0;
+        for class in 0..4 {
+            deficit += goal_cells[class].saturating_sub(pieces[class]);
+        }
+
+        outside.max(total_distance.div_ceil(max_gain)).max(deficit)
+    }
+
+    // an optimal plan, None if the budget or `limits` ran out first
+    pub fn plan(&self, board: &Board, player: usize, limits: &SearchLimits) -> Option<Plan> {
+        let mut b = board.clone();
+        let mut search = PlanSearch {
+            player,
+            limits,
+            max_nodes: self.max_nodes,
+            nodes: 0,
+            lower_bounds: HashMap::new(),
+            path: Vec::new(),
+        };
+
+        let mut bound = self.lower_bound(&b, player);
+        loop {
+            match self.ida(&mut search, &mut b, 0, bound) {
+                Some(true) => {
+                    return Some(Plan {
+                        actions: search.path,
+                        nodes: search.nodes,
+                    });
+                },
+                Some(false) => bound += 1,
+                None => return None,
+            }
+        }
+    }
+
+    // Some(true) if the camp can be filled within `bound` moves, None if the search ran out of budget
+    fn ida(&self, search: &mut PlanSearch, board: &mut Board, g: usize, bound: usize) -> Option<bool> {
+        let key = zobrist::hash(board, search.player);
+        let mut h = self.lower_bound(board, search.player);
+        if h == 0 {
+            return Some(true);
+        }
+        if let Some(&x) = search.lower_bounds.get(&key) {
+            h = h.max(x);
+        }
+        if g + h > bound {
+            return Some(false);
+        }
+
+        search.nodes += 1;
+        if search.nodes >= search.max_nodes || search.limits.should_stop(search.nodes) {
+            return None;
+        }
+
+        let player = search.player;
+        let mut actions = board.generate_actions_all(player);
+        actions.sort_by_key(|a| (-a.gain(player), a.from_x, a.from_y, a.to_x, a.to_y));
+        for &action in actions.iter() {
+            board.perform_action(action);
+            search.path.push(action);
+            let result = self.ida(search, board, g + 1, bound);
+            board.undo_action(action);
+            match result {
+                Some(true) => return Some(true),
+                Some(false) => {
+                    search.path.pop();
+                },
+                None => return None,
+            }
+        }
+
+        // nothing within bound - g moves from here
+        let entry = search.lower_bounds.entry(key).or_insert(0);
+        *entry = (*entry).max(bound - g + 1);
+        Some(false)
+    }
+
+    // always plays the move with the best lower bound afterwards, ties go to the longest forward move
+    // positions already seen are skipped so it cannot shuffle back and forth
+    // used as a baseline for the optimal planner, None if the camp is not filled within `max_moves`
+    pub fn greedy_plan(&self, board: &Board, player: usize, max_moves: usize) -> Option<Vec<Action>> {
+        let mut b = board.clone();
+        let mut seen = HashSet::new();
+        seen.insert(zobrist::hash(&b, player));
+        let mut actions = Vec::new();
+        while self.lower_bound(&b, player) > 0 {
+            if actions.len() >= max_moves {
+                return None;
+            }
+            let mut best = None;
+            for action in b.generate_actions_all(player) {
+                b.perform_action(action);
+                let key = (self.lower_bound(&b, player), -action.gain(player), action.from_x, action.from_y, action.to_x, action.to_y);
+                if !seen.contains(&zobrist::hash(&b, player)) && best.map_or(true, |(x, _)| key < x) {
+                    best = Some((key, action));
+                }
+                b.undo_action(action);
+            }
+            let (_, action) = best?;
+            b.perform_action(action);
+            seen.insert(zobrist::hash(&b, player));
+            actions.push(action);
+        }
+        Some(actions)
+    }
+}
+
+impl Solver for CampPlanner {
+    // the opponent does not move, `NotFound` if no plan was found within budget
+    fn solve_with_limits(&self, board: &Board, next_player: usize, limits: &SearchLimits) -> Result<SearchResult, SolverError> {
+        let start = Instant::now();
+        let plan = self.plan(board, next_player, limits).ok_or(SolverError::NotFound)?;
+        if plan.actions.is_empty() {
+            return Err(SolverError::NotFound);
+        }
+
+        let mut result = SearchResult::new(plan.actions[0]);
+        result.depth = plan.actions.len();
+        result.nodes = plan.nodes;
+        result.pv = plan.actions;
+        result.elapsed = start.elapsed();
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{HashMap, VecDeque};
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use crate::board::board::Board;
+    use crate::board::zobrist;
+    use crate::constants::{BLUE, RED};
+    use crate::solver::search_limits::SearchLimits;
+    use super::CampPlanner;
+
+    // `player` in its camp, the opponent's pieces scattered outside it
+    fn random_goal_position(rng: &mut StdRng, size: usize, camp: usize, player: usize) -> Board {
+        let mut board = Board::with_camp(size, camp);
+        let goal = if player == RED { BLUE } else { RED };
+        // `Board::with_camp` puts each side in the other's goal, swap the armies
+        for i in 0..size {
+            for j in 0..size {
+                board.data[i][j] = if board.data[i][j] == goal { player } else { 0 };
+            }
+        }
+        let mut placed = 0;
+        while placed < camp * camp {
+            let (x, y) = (rng.gen_range(0..size), rng.gen_range(0..size));
+            if board.data[x][y] == 0 && !board.is_in_goal(player, x, y) {
+                board.data[x][y] = 3 - player;
+                placed += 1;
+            }
+        }
+        board
+    }
+
+    // moves are reversible, so a search from the filled camp gives every position's exact distance to it
+    fn exact_distances(start: &Board, player: usize) -> Vec<(Board, usize)> {
+        let mut seen = HashMap::new();
+        let mut queue = VecDeque::new();
+        let mut result = Vec::new();
+        seen.insert(zobrist::hash(start, player), 0);
+        queue.push_back((start.clone(), 0));
+        while let Some((board, distance)) = queue.pop_front() {
+            for action in board.generate_actions_all(player) {
+                let mut next = board.clone();
+                next.perform_action(action);
+                if seen.insert(zobrist::hash(&next, player), distance + 1).is_none() {
+                    queue.push_back((next, distance + 1));
+                }
+            }
+            result.push((board, distance));
+        }
+        result
+    }
+
+    #[test]
+    fn lower_bound_is_admissible() {
+        let planner = CampPlanner::default();
+        let mut rng = StdRng::seed_from_u64(0);
+        for (size, camp) in [(4, 2), (5, 2)] {
+            for player in [RED, BLUE] {
+                for _ in 0..4 {
+                    let goal = random_goal_position(&mut rng, size, camp, player);
+                    for (board, distance) in exact_distances(&goal, player) {
+                        assert!(planner.lower_bound(&board, player) <= distance, "bound above {} moves\n{}", distance, board);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn plans_are_optimal() {
+        let planner = CampPlanner::default();
+        let mut rng = StdRng::seed_from_u64(1);
+        for player in [RED, BLUE] {
+            let goal = random_goal_position(&mut rng, 5, 2, player);
+            for (board, distance) in exact_distances(&goal, player).into_iter().step_by(97) {
+                let plan = planner.plan(&board, player, &SearchLimits::default()).unwrap();
+                assert_eq!(plan.actions.len(), distance, "\n{}", board);
+            }
+        }
+    }
+}
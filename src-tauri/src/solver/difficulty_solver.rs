@@ -0,0 +1,165 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Serialize, Deserialize};
+use crate::board::action::Action;
+use crate::board::board::Board;
+use crate::evaluation::manhattan::ManhattanEvaluation;
+use crate::evaluation::score::{is_loss, is_win, win_in, Score};
+use crate::solver::alpha_beta_solver::{AlphaBetaSolver, AlphaBetaSolverConfig};
+use crate::solver::mix_solver::MixSolver;
+use crate::solver::search_limits::SearchLimits;
+use crate::solver::solver::{SearchResult, Solver, SolverError};
+use crate::solver::transposition_table::TranspositionTable;
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub enum Difficulty {
+    Beginner,
+    Easy,
+    Medium,
+    Hard,
+    // full strength `MixSolver`
+    Expert,
+}
+
+#[derive(Clone, Debug)]
+pub struct DifficultySettings {
+    // depth of the search behind every root move
+    pub max_depth: usize,
+    pub time_limit: Option<Duration>,
+    // moves within `margin` of the best are drawn with probability exp((score - best) / temperature)
+    // a temperature of 0 always takes the best
+    pub margin: Score,
+    pub temperature: f64,
+    // chance of playing a random move from outside the margin instead
+    pub blunder_rate: f64,
+}
+
+impl Difficulty {
+    pub fn settings(&self) -> DifficultySettings {
+        let (max_depth, time_ms, margin, temperature, blunder_rate) = match self {
+            Difficulty::Beginner => (1, 200, 20, 8.0, 0.2),
+            Difficulty::Easy => (2, 500, 10, 4.0, 0.1),
+            Difficulty::Medium => (3, 1000, 4, 2.0, 0.03),
+            Difficulty::Hard => (4, 2000, 2, 0.5, 0.0),
+            Difficulty::Expert => (5, 5000, 0, 0.0, 0.0),
+        };
+        DifficultySettings {
+            max_depth,
+            time_limit: Some(Duration::from_millis(time_ms)),
+            margin,
+            temperature,
+            blunder_rate,
+        }
+    }
+
+    // the engine playing at this level
+    pub fn solver(&self, seed: u64) -> Arc<dyn Solver + Send + Sync> {
+        match self {
+            Difficulty::Expert => Arc::new(MixSolver::default()),
+            _ => Arc::new(DifficultySolver::new(self.settings(), seed)),
+        }
+    }
+}
+
+// searches every root move to a reduced depth, then picks among the near-best by temperature
+// and now and then plays a worse move on purpose
+pub struct DifficultySolver {
+    pub settings: DifficultySettings,
+    // shared by the searches behind the root moves and kept between moves
+    pub tt: Arc<TranspositionTable>,
+    rng: Mutex<StdRng>,
+}
+
+impl DifficultySolver {
+    pub fn new(settings: DifficultySettings, seed: u64) -> DifficultySolver {
+        DifficultySolver {
+            settings,
+            tt: Arc::new(TranspositionTable::new(AlphaBetaSolverConfig::default().tt_size_log2)),
+            rng: Mutex::new(StdRng::seed_from_u64(seed)),
+        }
+    }
+
+    // scores of every move for the side to move
+    fn score_moves(&self, board: &Board, next_player: usize, limits: &SearchLimits, nodes: &mut u64) -> Vec<(Action, Score)> {
+        let mut searcher = AlphaBetaSolver::new(Box::new(ManhattanEvaluation), self.settings.max_depth.max(2) - 1);
+        searcher.tt = Some(self.tt.clone());
+        let mut actions = board.generate_actions_all(next_player);
+        actions.sort_by_key(|a| (-a.gain(next_player), a.from_x, a.from_y, a.to_x, a.to_y));
+
+        let mut b = board.clone();
+        let mut result = Vec::new();
+        for &action in actions.iter() {
+            b.perform_action(action);
+            let static_score = -searcher.evaluator.evaluate(&b, 3 - next_player);
+            let score = if b.is_game_over().is_some() {
+                win_in(1)
+            } else if self.settings.max_depth <= 1 {
+                static_score
+            } else {
+                match searcher.solve_with_limits(&b, 3 - next_player, limits) {
+                    // depth 0 means the limits hit before the first iteration finished
+                    Ok(x) if x.depth > 0 => {
+                        *nodes += x.nodes;
+                        // the reply's win or loss distance counts from the position after our move, one ply further
+                        let score = -(x.score as Score);
+                        if is_win(score) {
+                            score - 1
+                        } else if is_loss(score) {
+                            score + 1
+                        } else {
+                            score
+                        }
+                    },
+                    _ => static_score,
+                }
+            };
+            b.undo_action(action);
+            result.push((action, score));
+        }
+        result
+    }
+}
+
+impl Solver for DifficultySolver {
+    fn solve_with_limits(&self, board: &Board, next_player: usize, limits: &SearchLimits) -> Result<SearchResult, SolverError> {
+        let start = Instant::now();
+        let mut limits = limits.clone();
+        if let Some(time) = self.settings.time_limit {
+            let deadline = start + time;
+            limits.deadline = Some(limits.deadline.map_or(deadline, |x| x.min(deadline)));
+        }
+
+        let mut nodes = 0;
+        let scored = self.score_moves(board, next_player, &limits, &mut nodes);
+        let best = scored.iter().map(|x| x.1).max().ok_or(SolverError::NoLegalMoves)?;
+        let (near, far): (Vec<_>, Vec<_>) = scored.into_iter().partition(|x| x.1 >= best - self.settings.margin);
+
+        let mut rng = self.rng.lock().unwrap();
+        let (action, score) = if !far.is_empty() && rng.gen_bool(self.settings.blunder_rate) {
+            far[rng.gen_range(0..far.len())]
+        } else if self.settings.temperature <= 0.0 {
+            *near.iter().find(|x| x.1 == best).unwrap()
+        } else {
+            let weights: Vec<f64> = near.iter().map(|x| ((x.1 - best) as f64 / self.settings.temperature).exp()).collect();
+            let mut r = rng.gen::<f64>() * weights.iter().sum::<f64>();
+            let mut chosen = near[near.len() - 1];
+            for (index, w) in weights.iter().enumerate() {
+                if r < *w {
+                    chosen = near[index];
+                    break;
+                }
+                r -= w;
+            }
+            chosen
+        };
+
+        let mut result = SearchResult::new(action);
+        result.score = score as f64;
+        result.depth = self.settings.max_depth;
+        result.nodes = nodes;
+        result.elapsed = start.elapsed();
+        Ok(result)
+    }
+}
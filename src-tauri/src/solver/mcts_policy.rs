@@ -0,0 +1,55 @@
+use crate::board::action::Action;
+use crate::board::board::Board;
+use crate::evaluation::evaluation::Evaluation;
+
+// prior probabilities for the moves of a position, used by PUCT selection
+pub trait PolicySource: Send + Sync {
+    // one prior per action, summing to 1
+    fn priors(&self, board: &Board, next_player: usize, actions: &[Action]) -> Vec<f64>;
+}
+
+pub struct UniformPolicy;
+
+impl PolicySource for UniformPolicy {
+    fn priors(&self, _board: &Board, _next_player: usize, actions: &[Action]) -> Vec<f64> {
+        vec![1.0 / actions.len() as f64; actions.len()]
+    }
+}
+
+// softmax over how much each move improves the evaluation for the side to move
+pub struct EvaluationPolicy {
+    pub evaluation: Box<dyn Evaluation>,
+    // in evaluation units, lower is greedier
+    pub temperature: f64,
+}
+
+impl EvaluationPolicy {
+    pub fn new(evaluation: Box<dyn Evaluation>, temperature: f64) -> Self {
+        EvaluationPolicy {
+            evaluation,
+            temperature,
+        }
+    }
+}
+
+impl PolicySource for EvaluationPolicy {
+    fn priors(&self, board: &Board, next_player: usize, actions: &[Action]) -> Vec<f64> {
+        let mut b = board.clone();
+        let before = self.evaluation.evaluate(&b, next_player) as f64;
+        let deltas: Vec<f64> = actions.iter().map(|&action| {
+            b.perform_action(action);
+            let after = -self.evaluation.evaluate(&b, 3 - next_player) as f64;
+            b.undo_action(action);
+            after - before
+        }).collect();
+
+        softmax(&deltas, self.temperature)
+    }
+}
+
+pub fn softmax(values: &[f64], temperature: f64) -> Vec<f64> {
+    let max = values.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    let weights: Vec<f64> = values.iter().map(|x| ((x - max) / temperature.max(1e-9)).exp()).collect();
+    let total: f64 = weights.iter().sum();
+    weights.iter().map(|x| x / total).collect()
+}
@@ -1,67 +1,440 @@
-use std::cell::RefCell;
-use std::rc::{Rc, Weak};
+use std::collections::{HashSet, VecDeque};
+use std::ops::Range;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant};
 use crate::board::action::Action;
 use crate::board::board::Board;
 use crate::constants::{BLUE, RED};
-use crate::simulator::naive_simulator::NaiveSimulator;
-use crate::solver::solver::Solver;
+use crate::evaluation::evaluation::Evaluation;
+use crate::evaluation::manhattan::ManhattanEvaluation;
+use crate::simulator::simulator::{PlayoutCutoff, PlayoutPolicy, Simulator};
+use crate::solver::mcts_policy::{PolicySource, UniformPolicy};
+use crate::solver::search_limits::SearchLimits;
+use crate::solver::solver::{ProvenOutcome, SearchResult, Solver, SolverError};
+use crate::tablebase::tablebase::{Tablebase, TablebaseValue};
 
+// nodes live in one arena and find their children through a block of child indices in a second one,
+// boards are replayed along the selection path
+// every field is atomic so that several threads can search the same tree
+#[derive(Default)]
 pub struct MCTSNode {
-    pub visit: usize,
-    pub win: usize,
-    pub score: f64,
+    // the move leading here, meaningless for the root
+    action: AtomicU32,
+    // start, used entries and capacity of the child index block in one word, so readers never see
+    // a count that does not match the block; stored last, a thread that sees it also sees the children
+    children: AtomicU64,
+    // winner of the position in the low bits, EXPANDED once a thread claimed the node for expansion,
+    // PROVEN_WIN or PROVEN_LOSS for the player who made `action` once the result is known, with the plies to the end above,
+    // WIDENING while a thread adds children and FULL once every move has its child
+    state: AtomicU32,
+    pub visit: AtomicU32,
+    // wins of the player who made `action`, as f64 bits since cut off playouts score fractions of a win
+    win: AtomicU64,
+    // all-moves-as-first: playouts through the parent in which the same player made `action` at any later point
+    pub amaf_visit: AtomicU32,
+    amaf_win: AtomicU64,
+    // probability the policy gave `action`, as f32 bits
+    prior: AtomicU32,
+}
 
-    // will-not-change attributes
-    pub board: Board,
-    pub next_player: usize,
-    pub player: usize,
-    pub action: Action,
-    pub depth: usize,
-    pub game_over: Option<usize>,
+fn add_f64(value: &AtomicU64, x: f64) {
+    let _ = value.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |bits| Some((f64::from_bits(bits) + x).to_bits()));
+}
+
+const EXPANDED: u32 = 1 << 8;
+const PROVEN_WIN: u32 = 1 << 9;
+const PROVEN_LOSS: u32 = 1 << 10;
+const WIDENING: u32 = 1 << 11;
+const FULL: u32 = 1 << 12;
+const PLIES_SHIFT: u32 = 16;
 
-    pub parent: Option<Weak<RefCell<MCTSNode>>>,
-    pub children: Vec<Rc<RefCell<MCTSNode>>>,
+fn encode_action(action: Action) -> u32 {
+    u32::from_le_bytes([action.from_x, action.from_y, action.to_x, action.to_y])
+}
+
+fn decode_action(value: u32) -> Action {
+    let [from_x, from_y, to_x, to_y] = value.to_le_bytes();
+    Action { from_x, from_y, to_x, to_y }
 }
 
 impl MCTSNode {
-    pub fn new(board: &Board, next_player: usize) -> MCTSNode {
-        MCTSNode {
-            visit: 0,
-            win: 0,
-            score: 0.0,
+    // only for nodes no other thread can reach yet
+    fn init(&self, action: Action, game_over: Option<usize>) {
+        self.action.store(encode_action(action), Ordering::Relaxed);
+        self.state.store(game_over.unwrap_or(0) as u32, Ordering::Relaxed);
+    }
+
+    pub fn action(&self) -> Action {
+        decode_action(self.action.load(Ordering::Relaxed))
+    }
+
+    pub fn visit(&self) -> u32 {
+        self.visit.load(Ordering::Relaxed)
+    }
+
+    pub fn win(&self) -> f64 {
+        f64::from_bits(self.win.load(Ordering::Relaxed))
+    }
+
+    pub fn add_win(&self, win: f64) {
+        add_f64(&self.win, win);
+    }
+
+    pub fn amaf_visit(&self) -> u32 {
+        self.amaf_visit.load(Ordering::Relaxed)
+    }
+
+    pub fn amaf_win(&self) -> f64 {
+        f64::from_bits(self.amaf_win.load(Ordering::Relaxed))
+    }
+
+    pub fn add_amaf_win(&self, win: f64) {
+        add_f64(&self.amaf_win, win);
+    }
+
+    pub fn prior(&self) -> f64 {
+        f32::from_bits(self.prior.load(Ordering::Relaxed)) as f64
+    }
+
+    fn set_prior(&self, prior: f64) {
+        self.prior.store((prior as f32).to_bits(), Ordering::Relaxed);
+    }
+
+    pub fn has_children(&self) -> bool {
+        self.child_count() > 0
+    }
+
+    pub fn child_count(&self) -> usize {
+        self.child_block().len()
+    }
+
+    // the used entries of the child index block
+    fn child_block(&self) -> Range<usize> {
+        let children = self.children.load(Ordering::Acquire);
+        let first = (children & 0xffff_ffff) as usize;
+        let count = (children >> 32 & 0xffff) as usize;
+        first..first + count
+    }
+
+    // entries of the child index block, used or not
+    pub fn capacity(&self) -> usize {
+        (self.children.load(Ordering::Relaxed) >> 48) as usize
+    }
+
+    fn set_children(&self, first: usize, count: usize, capacity: usize) {
+        self.children.store(first as u64 | (count as u64) << 32 | (capacity as u64) << 48, Ordering::Release);
+    }
+
+    // true once every legal move has a child
+    pub fn is_full(&self) -> bool {
+        self.state.load(Ordering::Relaxed) & FULL != 0
+    }
+
+    // everything but the children
+    fn copy_from(&self, source: &MCTSNode) {
+        self.action.store(source.action.load(Ordering::Relaxed), Ordering::Relaxed);
+        self.state.store(source.state.load(Ordering::Relaxed) & !WIDENING, Ordering::Relaxed);
+        self.visit.store(source.visit(), Ordering::Relaxed);
+        self.win.store(source.win.load(Ordering::Relaxed), Ordering::Relaxed);
+        self.amaf_visit.store(source.amaf_visit(), Ordering::Relaxed);
+        self.amaf_win.store(source.amaf_win.load(Ordering::Relaxed), Ordering::Relaxed);
+        self.prior.store(source.prior.load(Ordering::Relaxed), Ordering::Relaxed);
+    }
+
+    pub fn winner(&self) -> Option<usize> {
+        match self.state.load(Ordering::Relaxed) & 0xff {
+            0 => None,
+            x => Some(x as usize),
+        }
+    }
+
+    // the result for the player who made `action`, if proven
+    pub fn proven(&self) -> Option<ProvenOutcome> {
+        let state = self.state.load(Ordering::Relaxed);
+        if state & PROVEN_WIN != 0 {
+            Some(ProvenOutcome::Win)
+        } else if state & PROVEN_LOSS != 0 {
+            Some(ProvenOutcome::Loss)
+        } else {
+            None
+        }
+    }
+
+    // plies from this position to the end of the game, for proven nodes
+    pub fn proven_plies(&self) -> u32 {
+        self.state.load(Ordering::Relaxed) >> PLIES_SHIFT
+    }
+
+    // the first proof wins if threads race
+    fn prove(&self, outcome: ProvenOutcome, plies: u32) {
+        let flag = if outcome == ProvenOutcome::Win { PROVEN_WIN } else { PROVEN_LOSS };
+        let _ = self.state.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |state| {
+            if state & (PROVEN_WIN | PROVEN_LOSS) != 0 {
+                None
+            } else {
+                Some(state | flag | plies << PLIES_SHIFT)
+            }
+        });
+    }
+
+    // true for exactly one caller, who then creates the children
+    fn claim(&self) -> bool {
+        self.state.fetch_or(EXPANDED, Ordering::Relaxed) & EXPANDED == 0
+    }
+}
+
+// grows in chunks that never move, so references stay valid while other threads allocate
+const CHUNK_BITS: usize = 14;
+const CHUNK_SIZE: usize = 1 << CHUNK_BITS;
+const MAX_CHUNKS: usize = 1 << 14;
+
+struct ChunkedArena<T> {
+    chunks: Vec<OnceLock<Box<[T]>>>,
+    len: AtomicUsize,
+}
+
+impl<T: Default> ChunkedArena<T> {
+    fn new() -> Self {
+        ChunkedArena {
+            chunks: (0..MAX_CHUNKS).map(|_| OnceLock::new()).collect(),
+            len: AtomicUsize::new(0),
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.len.load(Ordering::Relaxed)
+    }
+
+    fn get(&self, index: usize) -> &T {
+        let chunk = self.chunks[index >> CHUNK_BITS].get().unwrap();
+        &chunk[index & (CHUNK_SIZE - 1)]
+    }
+
+    // `count` contiguous fresh items, returns the first
+    fn alloc(&self, count: usize) -> usize {
+        let first = self.len.fetch_add(count, Ordering::Relaxed);
+        let last = (first + count).max(1) - 1;
+        assert!(last >> CHUNK_BITS < MAX_CHUNKS, "MCTS tree is full");
+        for chunk in first >> CHUNK_BITS..=last >> CHUNK_BITS {
+            self.chunks[chunk].get_or_init(|| (0..CHUNK_SIZE).map(|_| T::default()).collect());
+        }
+        first
+    }
+}
+
+pub struct MCTSTree {
+    nodes: ChunkedArena<MCTSNode>,
+    // child indices, every node with children owns one block
+    edges: ChunkedArena<AtomicU32>,
+    // position of the root, node 0
+    pub board: Board,
+    pub next_player: usize,
+}
+
+impl MCTSTree {
+    pub fn new(board: &Board, next_player: usize) -> MCTSTree {
+        let tree = MCTSTree {
+            nodes: ChunkedArena::new(),
+            edges: ChunkedArena::new(),
             board: board.clone(),
             next_player,
-            player: 3 - next_player,
-            action: Action::from_usize(0, 0, 0, 0),
-            depth: 0,
-            game_over: board.is_game_over(),
-            parent: None,
-            children: Vec::with_capacity(100),
+        };
+        let root = tree.alloc(1);
+        tree.node(root).init(Action::from_usize(0, 0, 0, 0), board.is_game_over());
+        tree
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn node(&self, index: usize) -> &MCTSNode {
+        self.nodes.get(index)
+    }
+
+    // `count` contiguous fresh nodes, returns the first
+    fn alloc(&self, count: usize) -> usize {
+        self.nodes.alloc(count)
+    }
+
+    pub fn children(&self, node: usize) -> impl DoubleEndedIterator<Item = usize> + ExactSizeIterator + Clone + '_ {
+        self.node(node).child_block().map(move |x| self.edges.get(x).load(Ordering::Relaxed) as usize)
+    }
+
+    // appends the fresh nodes `children` to the children of `node`, one thread at a time
+    // a full index block is copied to one twice the size, at most `max_children`, the old block is left unused
+    fn add_children(&self, node: usize, children: Range<usize>, max_children: usize) {
+        let n = self.node(node);
+        let block = n.child_block();
+        let count = block.len() + children.len();
+        let mut first = block.start;
+        let mut capacity = n.capacity();
+        if count > capacity {
+            capacity = (2 * capacity).clamp(count, max_children.max(count));
+            first = self.edges.alloc(capacity);
+            for (offset, x) in block.clone().enumerate() {
+                self.edges.get(first + offset).store(self.edges.get(x).load(Ordering::Relaxed), Ordering::Relaxed);
+            }
+        }
+        for (offset, child) in children.enumerate() {
+            self.edges.get(first + block.len() + offset).store(child as u32, Ordering::Relaxed);
         }
+        n.set_children(first, count, capacity);
+    }
+
+    pub fn root(&self) -> &MCTSNode {
+        self.node(0)
     }
 
-    pub fn expand_from_node(node: Rc<RefCell<MCTSNode>>, action: Action) -> MCTSNode {
-        let mut new_board = node.borrow().board.clone();
-        new_board.perform_action(action);
-        let new_is_game_over = new_board.is_game_over();
+    // a new tree holding only the subtree of `node`, whose position is `board`
+    pub fn promote(&self, node: usize, board: &Board, next_player: usize) -> MCTSTree {
+        let tree = MCTSTree::new(board, next_player);
+        // (index in self, index in the new tree), breadth first so every child range stays contiguous
+        let mut queue = VecDeque::new();
+        queue.push_back((node, 0));
+        while let Some((from, to)) = queue.pop_front() {
+            tree.node(to).copy_from(self.node(from));
 
-        MCTSNode {
-            visit: 0,
-            win: 0,
-            score: 0.0,
-            board: new_board,
-            next_player: node.borrow().player,
-            player: node.borrow().next_player,
-            action,
-            depth: node.borrow().depth + 1,
-            game_over: new_is_game_over,
-            parent: Some(Rc::downgrade(&node)),
-            children: Vec::with_capacity(100)
+            let children = self.children(from);
+            let first = tree.alloc(children.len());
+            for (offset, child) in children.clone().enumerate() {
+                queue.push_back((child, first + offset));
+            }
+            tree.add_children(to, first..first + children.len(), children.len());
         }
+        tree
     }
 
-    pub fn has_children(&self) -> bool {
-        !self.children.is_empty()
+    // the node at most `max_plies` below the root whose position is `board`
+    pub fn find(&self, board: &Board, next_player: usize, max_plies: usize) -> Option<usize> {
+        let mut b = self.board.clone();
+        self.find_from(0, &mut b, self.next_player, board, next_player, max_plies)
+    }
+
+    fn find_from(&self, node: usize, b: &mut Board, player: usize, board: &Board, next_player: usize, max_plies: usize) -> Option<usize> {
+        if player == next_player && b == board {
+            return Some(node);
+        }
+        if max_plies == 0 {
+            return None;
+        }
+        for child in self.children(node) {
+            let action = self.node(child).action();
+            b.perform_action(action);
+            let result = self.find_from(child, b, 3 - player, board, next_player, max_plies - 1);
+            b.undo_action(action);
+            if result.is_some() {
+                return result;
+            }
+        }
+        None
+    }
+
+    pub fn most_visited_child(&self, node: usize) -> Option<usize> {
+        // the first of equally visited children, like the move order
+        self.children(node).rev().max_by_key(|&x| self.node(x).visit())
+    }
+
+    // the quickest proven win, otherwise the most visited move not proven lost, otherwise the slowest loss
+    pub fn best_child(&self, node: usize) -> Option<usize> {
+        let children = self.children(node);
+        let wins = children.clone().filter(|&x| self.node(x).proven() == Some(ProvenOutcome::Win));
+        if let Some(win) = wins.min_by_key(|&x| self.node(x).proven_plies()) {
+            return Some(win);
+        }
+        let open = children.clone().rev().filter(|&x| self.node(x).proven() != Some(ProvenOutcome::Loss));
+        if let Some(child) = open.max_by_key(|&x| self.node(x).visit()) {
+            return Some(child);
+        }
+        children.rev().max_by_key(|&x| self.node(x).proven_plies())
+    }
+
+    // adds the root move statistics of `other`, a tree of the same position searched independently
+    // proofs found in `other` carry over, a proof already here stays
+    pub fn merge_root(&self, other: &MCTSTree) {
+        for child in other.children(0) {
+            let source = other.node(child);
+            let action = source.action();
+            if let Some(target) = self.children(0).map(|x| self.node(x)).find(|x| x.action() == action) {
+                target.visit.fetch_add(source.visit(), Ordering::Relaxed);
+                target.add_win(source.win());
+                target.amaf_visit.fetch_add(source.amaf_visit(), Ordering::Relaxed);
+                target.add_amaf_win(source.amaf_win());
+                if let Some(outcome) = source.proven() {
+                    target.prove(outcome, source.proven_plies());
+                }
+            }
+        }
+        self.root().visit.fetch_add(other.root().visit(), Ordering::Relaxed);
+        if let Some(outcome) = other.root().proven() {
+            self.root().prove(outcome, other.root().proven_plies());
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ParallelMode {
+    // all threads grow one shared tree
+    Tree,
+    // every thread grows its own tree, the root move statistics are added up at the end
+    Root,
+}
+
+// weight of the AMAF win rate against the node's own, given its visits and AMAF visits
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum RaveSchedule {
+    // sqrt(k / (3n + k)), half and half at n = k / 3
+    Equivalence(f64),
+    // the weight minimising the mean squared error when AMAF values are off by the given bias
+    MinimumError(f64),
+}
+
+impl RaveSchedule {
+    pub fn beta(&self, visit: u32, amaf_visit: u32) -> f64 {
+        let n = visit as f64;
+        let amaf = amaf_visit as f64;
+        match *self {
+            RaveSchedule::Equivalence(k) => (k / (3.0 * n + k)).sqrt(),
+            RaveSchedule::MinimumError(bias) => amaf / (n + amaf + 4.0 * bias * bias * n * amaf),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SelectionRule {
+    // UCB1, every child gets `min_sim` visits first
+    Ucb,
+    // AlphaZero style, exploration weighted by the policy's priors
+    Puct,
+}
+
+// progressive widening: a node with n visits has max(initial, ceil(coefficient * n ^ exponent)) children
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct WideningSchedule {
+    pub initial: usize,
+    pub coefficient: f64,
+    pub exponent: f64,
+}
+
+impl Default for WideningSchedule {
+    fn default() -> Self {
+        WideningSchedule {
+            initial: 3,
+            coefficient: 1.0,
+            exponent: 0.5,
+        }
+    }
+}
+
+impl WideningSchedule {
+    pub fn children(&self, visit: u32) -> usize {
+        self.initial.max((self.coefficient * (visit as f64).powf(self.exponent)).ceil() as usize)
     }
 }
 
@@ -73,6 +446,53 @@ pub struct MCTSSolverConfig {
     pub times_per_sim: usize,
 
     pub ucb_constant: f64,
+
+    // budgets on top of `simulation_count` and the caller's limits, whichever runs out first
+    pub time_limit: Option<Duration>,
+    pub max_iterations: Option<usize>,
+    // stop as soon as the most visited root move cannot be overtaken in the budget left
+    pub early_stop: bool,
+
+    pub threads: usize,
+    pub parallel: ParallelMode,
+    // visits counted as losses on the selected path until the playouts are backed up,
+    // so that other threads of a shared tree spread over different lines
+    pub virtual_loss: usize,
+
+    // blends AMAF statistics from the playouts into selection, off if None
+    pub rave: Option<RaveSchedule>,
+
+    pub selection: SelectionRule,
+    // priors for PUCT, uniform if None
+    pub policy: Option<Arc<dyn PolicySource>>,
+    // exploration weight log((1 + N + base) / base) + constant, N the parent's visits
+    pub puct_constant: f64,
+    pub puct_base: f64,
+    // unvisited children are valued at the parent's win rate minus this
+    pub fpu_reduction: f64,
+
+    // add children in move order as the visits grow instead of all at once, off if None
+    pub widening: Option<WideningSchedule>,
+
+    // the simulator built for solvers not given one
+    pub playout: PlayoutPolicy,
+    // cut playouts off after this many plies and score them with `playout_evaluation`,
+    // mapped to a win probability with `playout_scale` evaluation units per e-fold of the odds
+    // the softmax policy scores its moves with `playout_evaluation` as well
+    pub playout_cutoff: Option<usize>,
+    pub playout_evaluation: Arc<dyn Evaluation>,
+    pub playout_scale: f64,
+}
+
+impl MCTSSolverConfig {
+    pub fn simulator(&self) -> Box<dyn Simulator> {
+        let cutoff = PlayoutCutoff {
+            max_plies: self.playout_cutoff,
+            evaluation: Some(self.playout_evaluation.clone()),
+            scale: self.playout_scale,
+        };
+        self.playout.simulator(cutoff, self.playout_evaluation.clone())
+    }
 }
 
 impl Default for MCTSSolverConfig {
@@ -84,181 +504,681 @@ impl Default for MCTSSolverConfig {
             simulation_count: 30000,
             times_per_sim: 5,
             ucb_constant: 1.414,
+            time_limit: None,
+            max_iterations: None,
+            early_stop: true,
+            threads: 1,
+            parallel: ParallelMode::Tree,
+            virtual_loss: 5,
+            rave: None,
+            selection: SelectionRule::Ucb,
+            policy: None,
+            puct_constant: 1.25,
+            puct_base: 19652.0,
+            fpu_reduction: 0.2,
+            widening: None,
+            playout: PlayoutPolicy::Uniform,
+            playout_cutoff: None,
+            playout_evaluation: Arc::new(ManhattanEvaluation),
+            playout_scale: 8.0,
         }
     }
 }
 
-pub struct MCTSSolverLogic {
+// iterations between early stopping checks and progress reports
+const CHECK_INTERVAL: usize = 64;
+
+// the best move of a running search, readable from other threads at any time
+#[derive(Clone, Default)]
+pub struct BestMoveReport {
+    current: Arc<Mutex<Option<SearchResult>>>,
+}
+
+impl BestMoveReport {
+    pub fn get(&self) -> Option<SearchResult> {
+        self.current.lock().unwrap().clone()
+    }
+
+    pub fn set(&self, result: SearchResult) {
+        *self.current.lock().unwrap() = Some(result);
+    }
+}
+
+// shared by all threads of one search
+#[derive(Default)]
+pub struct SearchState {
+    pub iterations: AtomicUsize,
+    pub stop: AtomicBool,
+}
+
+pub struct MCTSSolverLogic<S: Simulator = Box<dyn Simulator>> {
     pub config: MCTSSolverConfig,
 
     pub total_expand: usize,
     pub total_nodes: usize,
+    // iterations of this thread
+    pub iterations: usize,
+    pub max_depth: usize,
+
+    // covered leaves take the table's result instead of playouts
+    pub tablebase: Option<Arc<Tablebase>>,
+    pub report: Option<BestMoveReport>,
+
+    pub simulator: Arc<S>,
+    // moves and red's reward of the last playouts, kept for the AMAF update
+    playouts: Vec<(Vec<Action>, f64)>,
+    amaf_moves: HashSet<(Action, usize)>,
 }
 
 impl MCTSSolverLogic {
+    // with the simulator `config` selects
     pub fn new(config: MCTSSolverConfig) -> Self {
+        let simulator = Arc::new(config.simulator());
+        Self::with_simulator(config, simulator)
+    }
+}
+
+impl<S: Simulator> MCTSSolverLogic<S> {
+    pub fn with_simulator(config: MCTSSolverConfig, simulator: Arc<S>) -> Self {
         Self {
             config,
             total_nodes: 0,
             total_expand: 0,
+            iterations: 0,
+            max_depth: 0,
+            tablebase: None,
+            report: None,
+            simulator,
+            playouts: Vec::new(),
+            amaf_moves: HashSet::new(),
         }
     }
 
-    fn get_ucb(&self, node: Rc<RefCell<MCTSNode>>) -> f64 {
-        let k = self.config.ucb_constant;
-        let parent_visit = node.borrow().parent.as_ref().unwrap().upgrade().unwrap().borrow().visit as f64;
-        let visit = node.borrow().visit as f64;
+    // true if the runner-up among the root moves cannot catch up with the leader in the visits left
+    fn decided(&self, tree: &MCTSTree, state: &SearchState, target: usize, start_visit: u32, start: Instant, limits: &SearchLimits) -> bool {
+        let mut first = 0;
+        let mut second = 0;
+        for child in tree.children(0) {
+            let visit = tree.node(child).visit();
+            if visit > first {
+                second = first;
+                first = visit;
+            } else if visit > second {
+                second = visit;
+            }
+        }
 
-        k * (parent_visit.ln() / visit).sqrt()
+        let root_visit = tree.root().visit() as usize;
+        let mut remaining = target.saturating_sub(root_visit) as f64;
+        if let Some(max_nodes) = limits.max_nodes {
+            let iterations = max_nodes.saturating_sub(state.iterations.load(Ordering::Relaxed) as u64);
+            remaining = remaining.min((iterations * self.config.times_per_sim as u64) as f64);
+        }
+        if let Some(deadline) = limits.deadline {
+            // assume the visit rate so far holds
+            let elapsed = start.elapsed().as_secs_f64();
+            let left = deadline.saturating_duration_since(Instant::now()).as_secs_f64();
+            if elapsed > 0.0 {
+                let rate = root_visit.saturating_sub(start_visit as usize) as f64 / elapsed;
+                remaining = remaining.min(rate * left);
+            }
+        }
+
+        ((first - second) as f64) > remaining
     }
 
-    pub fn select(&self, node: Rc<RefCell<MCTSNode>>) -> Rc<RefCell<MCTSNode>> {
+    fn get_ucb(&self, parent_visit: u32, visit: u32) -> f64 {
+        let k = self.config.ucb_constant;
+        k * ((parent_visit as f64).ln() / visit as f64).sqrt()
+    }
+
+    pub fn select(&self, tree: &MCTSTree, node: usize) -> usize {
+        if self.config.selection == SelectionRule::Puct {
+            return self.select_puct(tree, node);
+        }
+        let parent_visit = tree.node(node).visit();
         let mut max_score = -1.0;
-        let mut max_index = 0;
+        let children = tree.children(node);
+        let mut max_index = children.clone().next().unwrap();
 
-        for i in 0..node.borrow().children.len() {
-            let n = node.borrow().children[i].clone();
-            if n.borrow().visit < self.config.min_sim {
-                return n;
+        for i in children {
+            let n = tree.node(i);
+            // MCTS-Solver: take a proven win, never choose a proven loss
+            match n.proven() {
+                Some(ProvenOutcome::Win) => return i,
+                Some(ProvenOutcome::Loss) => continue,
+                None => (),
+            }
+            let visit = n.visit();
+            if (visit as usize) < self.config.min_sim {
+                return i;
             }
 
-            let ucb = self.get_ucb(n.clone());
-            let visit = n.borrow().visit as f64;
-            let win_count = n.borrow().win as f64;
+            let mut value = n.win() / visit as f64;
+            if let Some(schedule) = &self.config.rave {
+                let amaf_visit = n.amaf_visit();
+                if amaf_visit > 0 {
+                    let beta = schedule.beta(visit, amaf_visit);
+                    value = (1.0 - beta) * value + beta * n.amaf_win() / amaf_visit as f64;
+                }
+            }
+            let score = value + self.get_ucb(parent_visit, visit);
+            if score > max_score {
+                max_score = score;
+                max_index = i;
+            }
+        }
 
-            let score = win_count / visit + ucb;
-            n.borrow_mut().score = score;
+        max_index
+    }
 
+    fn select_puct(&self, tree: &MCTSTree, node: usize) -> usize {
+        let parent = tree.node(node);
+        let parent_visit = parent.visit() as f64;
+        let exploration = ((1.0 + parent_visit + self.config.puct_base) / self.config.puct_base).ln() + self.config.puct_constant;
+        // the parent's win rate from the point of view of the side choosing among its children
+        let parent_value = if parent.visit() > 0 { 1.0 - parent.win() / parent_visit } else { 0.5 };
+        let first_play = parent_value - self.config.fpu_reduction;
+
+        let children = tree.children(node);
+        let mut max_score = f64::NEG_INFINITY;
+        let mut max_index = children.clone().next().unwrap();
+        for i in children {
+            let n = tree.node(i);
+            match n.proven() {
+                Some(ProvenOutcome::Win) => return i,
+                Some(ProvenOutcome::Loss) => continue,
+                None => (),
+            }
+            let visit = n.visit();
+            let value = if visit > 0 { n.win() / visit as f64 } else { first_play };
+            let score = value + exploration * n.prior() * parent_visit.sqrt() / (1.0 + visit as f64);
             if score > max_score {
                 max_score = score;
                 max_index = i;
             }
         }
-
-        node.borrow().children[max_index].clone()
+        max_index
     }
 
-    pub fn expand(&mut self, node: Rc<RefCell<MCTSNode>>) {
-        // println!("expand");
-        let next_player = node.borrow().next_player;
-        let actions = node.borrow().board.generate_actions_all(next_player);
+    // `board` is the position of `node`, false if another thread expands it
+    pub fn expand(&mut self, tree: &MCTSTree, node: usize, board: &mut Board, next_player: usize, depth: usize) -> bool {
+        if !tree.node(node).claim() {
+            return false;
+        }
+        let (actions, priors) = self.ordered_actions(board, next_player);
 
         self.total_expand += 1;
-        for &action in actions.iter() {
-            let new_node = MCTSNode::expand_from_node(node.clone(), action);
-            let new_node = Rc::new(RefCell::new(new_node));
-            node.borrow_mut().children.push(new_node);
-            self.total_nodes += 1;
+        self.max_depth = self.max_depth.max(depth + 1);
+        let n = tree.node(node);
+        let count = match &self.config.widening {
+            Some(schedule) => schedule.children(n.visit()).min(actions.len()),
+            None => actions.len(),
+        };
+        // nodes only for the moves widening allows so far, it adds the others as they are unlocked
+        let first = tree.alloc(count);
+        for offset in 0..count {
+            self.init_child(tree.node(first + offset), board, next_player, actions[offset], priors[offset]);
+        }
+        if count == actions.len() {
+            n.state.fetch_or(FULL, Ordering::Relaxed);
+        }
+        tree.add_children(node, first..first + count, actions.len());
+        true
+    }
+
+    // the moves in the order children are created, best first, with their priors
+    fn ordered_actions(&self, board: &Board, next_player: usize) -> (Vec<Action>, Vec<f64>) {
+        let mut actions = board.generate_actions_all(next_player);
+        actions.sort_by_key(|a| (-a.gain(next_player), a.from_x, a.from_y, a.to_x, a.to_y));
+        let priors = match (&self.config.selection, &self.config.policy) {
+            (SelectionRule::Puct, Some(policy)) => policy.priors(board, next_player, &actions),
+            _ => return (actions.clone(), UniformPolicy.priors(board, next_player, &actions)),
+        };
+        // widening should add the moves the policy likes first, the sort is stable so ties keep the move order
+        let mut order: Vec<usize> = (0..actions.len()).collect();
+        order.sort_by(|&a, &b| priors[b].partial_cmp(&priors[a]).unwrap());
+        (order.iter().map(|&x| actions[x]).collect(), order.iter().map(|&x| priors[x]).collect())
+    }
+
+    fn init_child(&mut self, child: &MCTSNode, board: &mut Board, next_player: usize, action: Action, prior: f64) {
+        board.perform_action(action);
+        let game_over = board.is_game_over();
+        child.init(action, game_over);
+        child.set_prior(prior);
+        if let Some(winner) = game_over {
+            child.prove(if winner == next_player { ProvenOutcome::Win } else { ProvenOutcome::Loss }, 0);
+        }
+        board.undo_action(action);
+        self.total_nodes += 1;
+    }
+
+    // progressive widening, `board` is the position of `node`: adds the next moves once the visits allow more children,
+    // or when every child so far is a proven loss
+    fn widen(&mut self, tree: &MCTSTree, node: usize, board: &mut Board, next_player: usize) {
+        let schedule = match &self.config.widening {
+            Some(x) => *x,
+            None => return,
+        };
+        let n = tree.node(node);
+        if n.is_full() || !n.has_children() {
+            return;
+        }
+        let mut target = schedule.children(n.visit());
+        if tree.children(node).all(|x| tree.node(x).proven() == Some(ProvenOutcome::Loss)) {
+            target = target.max(n.child_count() + 1);
         }
+        if target <= n.child_count() {
+            return;
+        }
+        // one thread at a time
+        if n.state.fetch_or(WIDENING, Ordering::Acquire) & WIDENING != 0 {
+            return;
+        }
+
+        let (actions, priors) = self.ordered_actions(board, next_player);
+        let have = n.child_count();
+        let count = target.min(actions.len());
+        let first = tree.alloc(count - have);
+        for offset in have..count {
+            self.init_child(tree.node(first + offset - have), board, next_player, actions[offset], priors[offset]);
+        }
+        if count == actions.len() {
+            n.state.fetch_or(FULL, Ordering::Relaxed);
+        }
+        tree.add_children(node, first..first + count - have, actions.len());
+        n.state.fetch_and(!WIDENING, Ordering::Release);
     }
 
-    pub fn update_mcts(&self, node: Rc<RefCell<MCTSNode>>, red_win: usize, total: usize) {
-        // println!("update mcts");
-        let mut n: Rc<RefCell<MCTSNode>> = node;
+    // bottom up along `path`: a child proven won for the side choosing proves its parent lost for the other side,
+    // children all proven lost prove the parent won
+    fn update_proven(&self, tree: &MCTSTree, path: &[usize]) {
+        for depth in (1..path.len()).rev() {
+            let parent = tree.node(path[depth - 1]);
+            let children = tree.children(path[depth - 1]);
+            match tree.node(path[depth]).proven() {
+                Some(ProvenOutcome::Win) => {
+                    let plies = children.filter(|&x| tree.node(x).proven() == Some(ProvenOutcome::Win)).map(|x| tree.node(x).proven_plies()).min().unwrap();
+                    parent.prove(ProvenOutcome::Loss, plies + 1);
+                },
+                Some(ProvenOutcome::Loss) => {
+                    // moves not added yet by progressive widening could still save the parent
+                    if !parent.is_full() || !children.clone().all(|x| tree.node(x).proven() == Some(ProvenOutcome::Loss)) {
+                        return;
+                    }
+                    let plies = children.map(|x| tree.node(x).proven_plies()).max().unwrap();
+                    parent.prove(ProvenOutcome::Win, plies + 1);
+                },
+                None => return,
+            }
+        }
+    }
+
+    fn virtual_loss(&self) -> u32 {
+        // taken back in full by the backup
+        self.config.virtual_loss.min(self.config.times_per_sim) as u32
+    }
 
-        loop {
-            if n.borrow().player == RED {
-                n.borrow_mut().win += red_win;
+    // `path` runs from the root to the simulated node, `root_player` made the move into the root
+    // the nodes on the path carry the virtual loss added during selection
+    // `red_win` is red's total reward over the `total` playouts
+    pub fn update_mcts(&self, tree: &MCTSTree, path: &[usize], root_player: usize, red_win: f64, total: usize) {
+        let virtual_loss = self.virtual_loss();
+        for (depth, &index) in path.iter().enumerate() {
+            let player = if depth % 2 == 0 { root_player } else { 3 - root_player };
+            let n = tree.node(index);
+            if player == RED {
+                n.add_win(red_win);
             } else {
-                n.borrow_mut().win += total - red_win;
+                n.add_win(total as f64 - red_win);
             }
-            n.borrow_mut().visit += total;
+            n.visit.fetch_add(total as u32 - virtual_loss, Ordering::Relaxed);
+        }
+    }
 
-            let temp = match n.borrow().parent {
-                None => {
-                    break;
-                },
-                Some(ref x) => {
-                    x.upgrade().unwrap().clone()
+    // red's reward summed over `times_per_sim` playouts
+    fn simulate(&mut self, board: &Board, next_player: usize, game_over: Option<usize>) -> f64 {
+        let total = self.config.times_per_sim as f64;
+        self.playouts.clear();
+        match game_over {
+            Some(player) => {
+                if player == RED {
+                    total
+                } else if player == BLUE {
+                    0.0
+                } else {
+                    panic!("this cannot happen");
                 }
-            };
-            n = temp;
+            },
+            None => {
+                let value = self.tablebase.as_ref().and_then(|x| x.probe(board, next_player));
+                let red_to_move = next_player == RED;
+                match value {
+                    Some(TablebaseValue::Win(_)) => if red_to_move { total } else { 0.0 },
+                    Some(TablebaseValue::Loss(_)) => if red_to_move { 0.0 } else { total },
+                    Some(TablebaseValue::Draw) => total / 2.0,
+                    None => {
+                        let mut red_win = 0.0;
+                        for _ in 0..self.config.times_per_sim {
+                            let mut played = Vec::new();
+                            let reward = self.simulator.simulate(board, next_player, &mut played);
+                            if self.config.rave.is_some() {
+                                self.playouts.push((played, reward));
+                            }
+                            red_win += reward;
+                        }
+                        red_win
+                    },
+                }
+            }
         }
     }
 
-    pub fn uct(&mut self, node: Rc<RefCell<MCTSNode>>) {
-        while node.borrow().visit < self.config.simulation_count {
-            let mut n = node.clone();
-            while n.borrow().has_children() {
-                n = self.select(n.clone());
+    // runs until the root has `target` visits, the limits say stop or another thread sets `state.stop`
+    pub fn uct(&mut self, tree: &MCTSTree, state: &SearchState, target: usize, limits: &SearchLimits) {
+        let root_player = 3 - tree.next_player;
+        let virtual_loss = self.virtual_loss();
+        let mut path = Vec::new();
+        let mut board = tree.board.clone();
+        let start = Instant::now();
+        let start_visit = tree.root().visit();
+
+        while (tree.root().visit() as usize) < target && !state.stop.load(Ordering::Relaxed) && tree.root().proven().is_none() {
+            if limits.should_stop(state.iterations.load(Ordering::Relaxed) as u64) {
+                break;
             }
-            // println!("node selected");
-
-            let visit = n.borrow().visit;
-            if visit >= self.config.max_sim {
-                let is_game_over = n.borrow().game_over.is_some();
-                if !is_game_over {
-                    self.expand(n.clone());
-                    n = self.select(n.clone());
+            if self.iterations % CHECK_INTERVAL == 0 && self.iterations > 0 {
+                if let Some(report) = &self.report {
+                    report.set(search_result(tree, self));
+                }
+                if self.config.early_stop && self.decided(tree, state, target, start_visit, start, limits) {
+                    break;
                 }
             }
+            self.iterations += 1;
+            state.iterations.fetch_add(1, Ordering::Relaxed);
+            board.clone_from(&tree.board);
+            let mut next_player = tree.next_player;
+            path.clear();
+            path.push(0);
+            tree.root().visit.fetch_add(virtual_loss, Ordering::Relaxed);
 
-            let mut red_win = 0;
-            match n.borrow().game_over {
-                Some(player) => {
-                    if player == RED {
-                        red_win = self.config.times_per_sim;
-                    } else if player == BLUE {
-                        red_win = 0
-                    } else {
-                        panic!("this cannot happen");
-                    }
+            let mut n = 0;
+            // proven nodes are not searched any further
+            while tree.node(n).has_children() && tree.node(n).proven().is_none() {
+                self.widen(tree, n, &mut board, next_player);
+                n = self.select(tree, n);
+                self.descend(tree, n, &mut board, &mut path);
+                next_player = 3 - next_player;
+            }
+
+            let node = tree.node(n);
+            let depth_allowed = limits.max_depth.map_or(true, |d| path.len() - 1 < d);
+            if node.visit() as usize >= self.config.max_sim && depth_allowed && node.winner().is_none() && node.proven().is_none() && self.expand(tree, n, &mut board, next_player, path.len() - 1) && tree.node(n).has_children() {
+                n = self.select(tree, n);
+                self.descend(tree, n, &mut board, &mut path);
+                next_player = 3 - next_player;
+            }
+
+            let red_win = match tree.node(n).proven() {
+                Some(outcome) => {
+                    self.playouts.clear();
+                    let mover_wins = outcome == ProvenOutcome::Win;
+                    if mover_wins == (3 - next_player == RED) { self.config.times_per_sim as f64 } else { 0.0 }
                 },
-                None => {
-                    for _ in 0..self.config.times_per_sim {
-                        if NaiveSimulator::default().simulate(&n.borrow().board, n.borrow().next_player) {
-                            red_win += 1;
-                        }
+                None => self.simulate(&board, next_player, tree.node(n).winner()),
+            };
+            self.update_mcts(tree, &path, root_player, red_win, self.config.times_per_sim);
+            self.update_proven(tree, &path);
+            if self.config.rave.is_some() {
+                self.update_amaf(tree, &path);
+            }
+        }
+    }
+
+    // for every node on `path` and every playout, the children whose move the player to move at the node
+    // made later on in the tree or in the playout
+    fn update_amaf(&mut self, tree: &MCTSTree, path: &[usize]) {
+        let player_at = |depth: usize| if depth % 2 == 0 { tree.next_player } else { 3 - tree.next_player };
+        let leaf = path.len() - 1;
+        for (played, red_reward) in self.playouts.iter() {
+            self.amaf_moves.clear();
+            for (ply, &action) in played.iter().enumerate() {
+                self.amaf_moves.insert((action, player_at(leaf + ply)));
+            }
+            for depth in (0..path.len()).rev() {
+                if depth < leaf {
+                    self.amaf_moves.insert((tree.node(path[depth + 1]).action(), player_at(depth)));
+                }
+                let player = player_at(depth);
+                let reward = if player == RED { *red_reward } else { 1.0 - red_reward };
+                for child in tree.children(path[depth]) {
+                    let n = tree.node(child);
+                    if self.amaf_moves.contains(&(n.action(), player)) {
+                        n.amaf_visit.fetch_add(1, Ordering::Relaxed);
+                        n.add_amaf_win(reward);
                     }
                 }
             }
-
-            self.update_mcts(n.clone(), red_win, self.config.times_per_sim);
         }
     }
+
+    fn descend(&self, tree: &MCTSTree, node: usize, board: &mut Board, path: &mut Vec<usize>) {
+        let n = tree.node(node);
+        n.visit.fetch_add(self.virtual_loss(), Ordering::Relaxed);
+        board.perform_action(n.action());
+        path.push(node);
+    }
+}
+
+// the playout settings in `config` only apply to the simulator `new` builds from them
+pub struct MCTSSolver<S: Simulator = Box<dyn Simulator>> {
+    pub config: MCTSSolverConfig,
+    pub tablebase: Option<Arc<Tablebase>>,
+    pub report: Option<BestMoveReport>,
+    pub simulator: Arc<S>,
 }
 
-fn get_most_visited_children(node: Rc<RefCell<MCTSNode>>) -> Rc<RefCell<MCTSNode>> {
-    let mut max_visit = 0;
-    let mut max_index = 0_usize;
-    for (index, item) in node.borrow().children.iter().enumerate() {
-        // let x = serde_json::to_string_pretty(&*item.borrow()).unwrap();
-        // println!("{}", x);
-        println!("visit of {}: {}", item.borrow().action, item.borrow().visit);
-        if item.borrow().visit > max_visit {
-            max_visit = item.borrow().visit;
-            max_index = index;
+impl MCTSSolver {
+    pub fn new(config: MCTSSolverConfig) -> Self {
+        let simulator = Arc::new(config.simulator());
+        MCTSSolver::with_simulator(config, simulator)
+    }
+}
+
+impl<S: Simulator> MCTSSolver<S> {
+    pub fn with_simulator(config: MCTSSolverConfig, simulator: Arc<S>) -> Self {
+        MCTSSolver {
+            config,
+            tablebase: None,
+            report: None,
+            simulator,
         }
     }
+}
 
-    node.borrow().children[max_index].clone()
+impl<S: Simulator> Solver for MCTSSolver<S> {
+    fn solve_with_limits(&self, board: &Board, next_player: usize, limits: &SearchLimits) -> Result<SearchResult, SolverError> {
+        let tree = MCTSTree::new(board, next_player);
+        search(&self.config, &self.simulator, &self.tablebase, &self.report, &tree, limits)
+    }
 }
 
-pub struct MCTSSolver {
+// keeps its tree between searches: when asked about a position within two plies of the last root
+// (our move and the reply) the matching subtree becomes the new root and everything else is dropped
+pub struct MCTSEngine<S: Simulator = Box<dyn Simulator>> {
     pub config: MCTSSolverConfig,
+    pub tablebase: Option<Arc<Tablebase>>,
+    // best move of the running or last search
+    pub report: BestMoveReport,
+    pub simulator: Arc<S>,
+    trees: Mutex<EngineTrees>,
 }
 
-impl MCTSSolver {
+#[derive(Default)]
+struct EngineTrees {
+    // tree of the last search
+    current: Option<MCTSTree>,
+    // the tree `current` was promoted from, it still holds the siblings of a pondered position
+    // so that a ponder miss finds the actual reply there
+    previous: Option<MCTSTree>,
+}
+
+impl MCTSEngine {
     pub fn new(config: MCTSSolverConfig) -> Self {
-        MCTSSolver {
-            config
+        let simulator = Arc::new(config.simulator());
+        MCTSEngine::with_simulator(config, simulator)
+    }
+}
+
+impl<S: Simulator> MCTSEngine<S> {
+    pub fn with_simulator(config: MCTSSolverConfig, simulator: Arc<S>) -> Self {
+        MCTSEngine {
+            config,
+            tablebase: None,
+            report: Default::default(),
+            simulator,
+            trees: Default::default(),
         }
     }
+
+    pub fn clear(&self) {
+        *self.trees.lock().unwrap() = Default::default();
+    }
+}
+
+impl<S: Simulator> Solver for MCTSEngine<S> {
+    fn solve_with_limits(&self, board: &Board, next_player: usize, limits: &SearchLimits) -> Result<SearchResult, SolverError> {
+        let mut trees = self.trees.lock().unwrap();
+        let reused = [&trees.current, &trees.previous].into_iter().flatten().find_map(|t| {
+            let node = t.find(board, next_player, 2)?;
+            Some(t.promote(node, board, next_player))
+        });
+        let tree = reused.unwrap_or_else(|| MCTSTree::new(board, next_player));
+        trees.previous = trees.current.replace(tree);
+        let tree = trees.current.as_ref().unwrap();
+        search(&self.config, &self.simulator, &self.tablebase, &Some(self.report.clone()), tree, limits)
+    }
 }
 
-impl Solver for MCTSSolver {
-    fn solve(&self, board: &Board, next_player: usize) -> Action {
-        let mut root = MCTSNode::new(board, next_player);
-        let root = Rc::new(RefCell::new(root));
+fn search<S: Simulator>(config: &MCTSSolverConfig, simulator: &Arc<S>, tablebase: &Option<Arc<Tablebase>>, report: &Option<BestMoveReport>, tree: &MCTSTree, limits: &SearchLimits) -> Result<SearchResult, SolverError> {
+    let start = Instant::now();
+    let board = tree.board.clone();
+    let next_player = tree.next_player;
+    if let Some(mut result) = tablebase.as_ref().and_then(|x| x.solve(&board, next_player)) {
+        // win rate of the table's move, like the searched score below
+        result.score = match result.proven {
+            Some(ProvenOutcome::Win) => 1.0,
+            Some(ProvenOutcome::Loss) => 0.0,
+            None => 0.5,
+        };
+        return Ok(result);
+    }
+
+    let mut limits = limits.clone();
+    if let Some(time) = config.time_limit {
+        let deadline = start + time;
+        limits.deadline = Some(limits.deadline.map_or(deadline, |x| x.min(deadline)));
+    }
+    if let Some(iterations) = config.max_iterations {
+        limits.max_nodes = Some(limits.max_nodes.map_or(iterations as u64, |x| x.min(iterations as u64)));
+    }
+
+    let new_logic = || {
+        let mut logic = MCTSSolverLogic::with_simulator(config.clone(), simulator.clone());
+        logic.tablebase = tablebase.clone();
+        logic
+    };
+    let mut logic = new_logic();
+    logic.report = report.clone();
+    // expand the root up front, so a search stopped right away still has moves to choose from
+    if !tree.root().has_children() && tree.root().winner().is_none() {
+        let mut b = board.clone();
+        logic.expand(tree, 0, &mut b, next_player, 0);
+    }
+    if !tree.root().has_children() {
+        return Err(SolverError::NoLegalMoves);
+    }
 
-        let mut logic = MCTSSolverLogic::new(self.config.clone());
-        // println!("logic created");
-        logic.uct(root.clone());
-        println!("done");
+    let threads = config.threads.max(1);
+    // visits a reused tree already has do not count
+    let target = match config.parallel {
+        ParallelMode::Tree => tree.root().visit() as usize + config.simulation_count,
+        // split between the trees
+        ParallelMode::Root => tree.root().visit() as usize + config.simulation_count / threads,
+    };
+    let state = SearchState::default();
 
-        let most_visited_node = get_most_visited_children(root.clone());
-        let action = most_visited_node.borrow().action;
+    thread::scope(|scope| {
+        let mut helpers = Vec::new();
+        for _ in 1..threads {
+            let state = &state;
+            let limits = &limits;
+            let mut helper = new_logic();
+            // the main thread reports and decides when to stop early
+            helper.config.early_stop = false;
+            helpers.push(scope.spawn(move || {
+                match config.parallel {
+                    ParallelMode::Tree => {
+                        helper.uct(tree, state, target, limits);
+                        (helper, None)
+                    },
+                    ParallelMode::Root => {
+                        let own = MCTSTree::new(&tree.board, next_player);
+                        let mut b = own.board.clone();
+                        helper.expand(&own, 0, &mut b, next_player, 0);
+                        helper.uct(&own, state, config.simulation_count / threads, limits);
+                        (helper, Some(own))
+                    },
+                }
+            }));
+        }
+
+        logic.uct(tree, &state, target, &limits);
+        state.stop.store(true, Ordering::Relaxed);
+
+        for helper in helpers {
+            let (helper, own) = helper.join().unwrap();
+            if let Some(own) = own {
+                tree.merge_root(&own);
+            }
+            logic.iterations += helper.iterations;
+            logic.total_expand += helper.total_expand;
+            logic.total_nodes += helper.total_nodes;
+            logic.max_depth = logic.max_depth.max(helper.max_depth);
+        }
+    });
+
+    let mut result = search_result(tree, &logic);
+    result.elapsed = start.elapsed();
+    if let Some(report) = report {
+        report.set(result.clone());
+    }
+    Ok(result)
+}
+
+// the most visited root move and the line below it
+fn search_result<S: Simulator>(tree: &MCTSTree, logic: &MCTSSolverLogic<S>) -> SearchResult {
+    let best = tree.best_child(0).unwrap();
+    let node = tree.node(best);
+
+    let mut result = SearchResult::new(node.action());
+    if node.visit() > 0 {
+        result.score = node.win() / node.visit() as f64;
+    }
+    if let Some(outcome) = node.proven() {
+        let moves = (node.proven_plies() as i32 + 2) / 2;
+        result.proven = Some(outcome);
+        result.score = if outcome == ProvenOutcome::Win { 1.0 } else { 0.0 };
+        result.moves_to_end = Some(if outcome == ProvenOutcome::Win { moves } else { -moves });
+    }
+    result.depth = logic.max_depth;
+    result.nodes = logic.iterations as u64;
 
-        action
+    let mut n = best;
+    while let Some(child) = tree.best_child(n) {
+        result.pv.push(tree.node(child).action());
+        n = child;
     }
+    result
 }
@@ -1,33 +1,56 @@
-use crate::board::action::Action;
+use std::sync::Arc;
+use std::thread;
 use crate::board::board::Board;
 use crate::evaluation::manhattan::ManhattanEvaluation;
 use crate::evaluation::naive_bonus::NaiveBonusEvaluation;
-use crate::solver::alpha_beta_solver::AlphaBetaSolver;
-use crate::solver::brute_force_search_solver::BruteForceSearchSolver;
-use crate::solver::solver::{MaybeSolver, Solver};
+use crate::solver::alpha_beta_solver::{AlphaBetaSolver, AlphaBetaSolverConfig};
+use crate::solver::proof_number_solver::ProofNumberSolver;
+use crate::solver::race_solver::RaceSolver;
+use crate::solver::search_limits::SearchLimits;
+use crate::solver::solver::{SearchResult, Solver, SolverError};
+use crate::solver::transposition_table::TranspositionTable;
 
-pub struct MixSolver;
+pub struct MixSolver {
+    // kept between moves, so pondering and consecutive searches share their work
+    pub tt: Arc<TranspositionTable>,
+}
 
 impl Default for MixSolver {
     fn default() -> Self {
-        MixSolver
+        MixSolver {
+            tt: Arc::new(TranspositionTable::new(AlphaBetaSolverConfig::default().tt_size_log2)),
+        }
     }
 }
 
 impl Solver for MixSolver {
-    fn solve(&self, board: &Board, next_player: usize) -> Action {
+    fn solve_with_limits(&self, board: &Board, next_player: usize, limits: &SearchLimits) -> Result<SearchResult, SolverError> {
         if board.is_separable() {
-            let bf_solver = BruteForceSearchSolver::new(4);
-            let result = bf_solver.solve(board, next_player);
-            if let Some(x) = result {
-                return x;
+            let race_solver = RaceSolver::default();
+            let result = race_solver.solve_with_limits(board, next_player, limits);
+            if result.is_ok() {
+                return result;
             }
 
             // let ab_solver1 = AlphaBetaSolver::new(Box::new(NaiveBonusEvaluation::default()));
             // return ab_solver1.solve(board, next_player);
         }
 
-        let ab_solver = AlphaBetaSolver::new(Box::new(ManhattanEvaluation), 5);
-        ab_solver.solve(board, next_player)
+        // exact endgame layer, only once the side to move could fill its camp within the proof horizon:
+        // every move brings at most one piece in, so with more pieces outside there is no win to find
+        let proof_solver = ProofNumberSolver::default();
+        if board.pieces_outside_goal(next_player) <= proof_solver.max_plies.div_ceil(2) {
+            if let Ok(result) = proof_solver.solve_with_limits(board, next_player, limits) {
+                return Ok(result);
+            }
+        }
+
+        let config = AlphaBetaSolverConfig {
+            threads: thread::available_parallelism().map(|x| x.get()).unwrap_or(1),
+            ..Default::default()
+        };
+        let mut ab_solver = AlphaBetaSolver::with_config(Box::new(ManhattanEvaluation), 5, config);
+        ab_solver.tt = Some(self.tt.clone());
+        ab_solver.solve_with_limits(board, next_player, limits)
     }
 }
@@ -1,5 +1,14 @@
 pub mod mcts_solver;
+pub mod mcts_policy;
 pub mod solver;
 pub mod alpha_beta_solver;
 pub mod brute_force_search_solver;
 pub mod mix_solver;
+pub mod transposition_table;
+pub mod search_limits;
+pub mod ponder;
+pub mod race_solver;
+pub mod camp_planner;
+pub mod proof_number_solver;
+pub mod book_solver;
+pub mod difficulty_solver;
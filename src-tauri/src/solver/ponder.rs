@@ -0,0 +1,122 @@
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+use crate::board::action::Action;
+use crate::board::board::Board;
+use crate::solver::search_limits::{CancellationToken, SearchLimits};
+use crate::solver::solver::{SearchResult, Solver, SolverError};
+
+struct PonderSearch {
+    // position after the predicted reply, the engine is to move
+    board: Board,
+    next_player: usize,
+    cancel: CancellationToken,
+    handle: JoinHandle<Result<SearchResult, SolverError>>,
+}
+
+// searches the predicted reply on the opponent's time
+// solvers that keep state between searches (such as a shared transposition table) benefit even when the prediction misses
+pub struct Ponderer {
+    pub solver: Arc<dyn Solver + Send + Sync>,
+    search: Option<PonderSearch>,
+}
+
+impl Ponderer {
+    pub fn new(solver: Arc<dyn Solver + Send + Sync>) -> Ponderer {
+        Ponderer {
+            solver,
+            search: None,
+        }
+    }
+
+    pub fn is_pondering(&self) -> bool {
+        self.search.is_some()
+    }
+
+    // `board` is the position right after the engine's move, `engine_player` the engine's side
+    pub fn start(&mut self, board: &Board, engine_player: usize, predicted: Action) {
+        self.stop();
+
+        let mut ponder_board = board.clone();
+        ponder_board.perform_action(predicted);
+        if ponder_board.is_game_over().is_some() {
+            return;
+        }
+
+        let limits = SearchLimits::default();
+        let cancel = limits.cancel.clone();
+        let solver = self.solver.clone();
+        let b = ponder_board.clone();
+        let handle = thread::spawn(move || solver.solve_with_limits(&b, engine_player, &limits));
+
+        self.search = Some(PonderSearch {
+            board: ponder_board,
+            next_player: engine_player,
+            cancel,
+            handle,
+        });
+    }
+
+    pub fn stop(&mut self) {
+        if let Some(search) = self.search.take() {
+            search.cancel.cancel();
+            let _ = search.handle.join();
+        }
+    }
+
+    pub fn solve(&mut self, board: &Board, next_player: usize) -> Result<SearchResult, SolverError> {
+        self.solve_with_limits(board, next_player, &SearchLimits::default())
+    }
+
+    // on a ponder hit the running search continues until `limits` runs out or it finishes by itself,
+    // otherwise it is dropped and a fresh search starts
+    pub fn solve_with_limits(&mut self, board: &Board, next_player: usize, limits: &SearchLimits) -> Result<SearchResult, SolverError> {
+        self.take(board, next_player).finish(board, next_player, limits)
+    }
+
+    // hands the ponder search for `board` over to the caller, or stops a ponder search on another position,
+    // so that the search can run without borrowing the ponderer
+    pub fn take(&mut self, board: &Board, next_player: usize) -> PendingSearch {
+        let hit = match &self.search {
+            Some(search) => search.next_player == next_player && search.board == *board,
+            None => false,
+        };
+        if !hit {
+            self.stop();
+        }
+        PendingSearch {
+            solver: self.solver.clone(),
+            search: self.search.take(),
+        }
+    }
+}
+
+// a search taken from a `Ponderer`, either the running ponder search or a fresh one with its solver
+pub struct PendingSearch {
+    solver: Arc<dyn Solver + Send + Sync>,
+    search: Option<PonderSearch>,
+}
+
+impl PendingSearch {
+    pub fn finish(self, board: &Board, next_player: usize, limits: &SearchLimits) -> Result<SearchResult, SolverError> {
+        let search = match self.search {
+            Some(x) => x,
+            None => return self.solver.solve_with_limits(board, next_player, limits),
+        };
+        while !search.handle.is_finished() && !limits.should_stop(0) {
+            thread::sleep(Duration::from_millis(5));
+        }
+        search.cancel.cancel();
+        match search.handle.join() {
+            Ok(result) => result,
+            // the search thread panicked, search again in this thread
+            Err(_) => self.solver.solve_with_limits(board, next_player, limits),
+        }
+    }
+}
+
+impl Drop for Ponderer {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
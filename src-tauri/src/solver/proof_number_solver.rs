@@ -0,0 +1,275 @@
+use std::collections::HashMap;
+use std::mem;
+use std::time::Instant;
+use crate::board::action::Action;
+use crate::board::board::Board;
+use crate::board::zobrist;
+use crate::evaluation::score::win_in;
+use crate::solver::camp_planner::CampPlanner;
+use crate::solver::search_limits::SearchLimits;
+use crate::solver::solver::{ProvenOutcome, SearchResult, Solver, SolverError};
+
+// proof and disproof numbers saturate here
+const INFINITE: u32 = u32::MAX / 2;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ProofStatus {
+    // the side to move wins within the ply limit
+    Proven,
+    // it does not, whatever it plays
+    Disproven,
+    // the node, memory or time budget ran out first
+    Unknown,
+}
+
+#[derive(Clone, Copy)]
+struct ProofEntry {
+    pn: u32,
+    dn: u32,
+}
+
+const PROVEN: ProofEntry = ProofEntry { pn: 0, dn: INFINITE };
+const DISPROVEN: ProofEntry = ProofEntry { pn: INFINITE, dn: 0 };
+
+pub struct ProofResult {
+    pub status: ProofStatus,
+    pub best_move: Option<Action>,
+    // shortest win in plies when proven
+    pub plies: usize,
+    pub pv: Vec<Action>,
+    pub nodes: u64,
+}
+
+// depth-first proof-number search (df-pn) of "the side to move fills its camp within `max_plies` plies"
+// the remaining plies are part of the table key, so there are no cycles to worry about
+pub struct ProofNumberSolver {
+    pub max_plies: usize,
+    // the search gives up once its table would grow past this
+    pub memory_mb: usize,
+    pub max_nodes: u64,
+}
+
+impl Default for ProofNumberSolver {
+    fn default() -> Self {
+        ProofNumberSolver {
+            max_plies: 7,
+            memory_mb: 64,
+            max_nodes: 200_000,
+        }
+    }
+}
+
+struct ProofSearch<'a> {
+    attacker: usize,
+    limits: &'a SearchLimits,
+    table: HashMap<(u64, u8), ProofEntry>,
+    max_entries: usize,
+    max_nodes: u64,
+    nodes: u64,
+    aborted: bool,
+    planner: CampPlanner,
+}
+
+impl<'a> ProofSearch<'a> {
+    // decided without searching: camp filled, no plies left, or the attacker cannot fill its camp in the moves it has left
+    fn leaf(&self, board: &Board, next_player: usize, remaining: usize) -> Option<ProofEntry> {
+        if let Some(winner) = board.is_game_over() {
+            return Some(if winner == self.attacker { PROVEN } else { DISPROVEN });
+        }
+        let attacker_moves = if next_player == self.attacker { remaining.div_ceil(2) } else { remaining / 2 };
+        if attacker_moves == 0 || self.planner.lower_bound(board, self.attacker) > attacker_moves {
+            return Some(DISPROVEN);
+        }
+        None
+    }
+
+    fn lookup(&self, board: &Board, next_player: usize, remaining: usize) -> ProofEntry {
+        if let Some(entry) = self.table.get(&(zobrist::hash(board, next_player), remaining as u8)) {
+            return *entry;
+        }
+        self.leaf(board, next_player, remaining).unwrap_or(ProofEntry { pn: 1, dn: 1 })
+    }
+
+    fn store(&mut self, board: &Board, next_player: usize, remaining: usize, entry: ProofEntry) {
+        if self.table.len() >= self.max_entries {
+            self.aborted = true;
+            return;
+        }
+        self.table.insert((zobrist::hash(board, next_player), remaining as u8), entry);
+    }
+
+    fn actions(&self, board: &Board, next_player: usize) -> Vec<Action> {
+        let mut actions = board.generate_actions_all(next_player);
+        actions.sort_by_key(|a| (-a.gain(next_player), a.from_x, a.from_y, a.to_x, a.to_y));
+        actions
+    }
+
+    // expands the node until its proof number reaches `th_pn` or its disproof number reaches `th_dn`
+    fn mid(&mut self, board: &mut Board, next_player: usize, remaining: usize, th_pn: u32, th_dn: u32) {
+        self.nodes += 1;
+        if self.nodes >= self.max_nodes || self.limits.should_stop(self.nodes) {
+            self.aborted = true;
+            return;
+        }
+        if let Some(entry) = self.leaf(board, next_player, remaining) {
+            self.store(board, next_player, remaining, entry);
+            return;
+        }
+
+        let actions = self.actions(board, next_player);
+        if actions.is_empty() {
+            self.store(board, next_player, remaining, DISPROVEN);
+            return;
+        }
+        let or_node = next_player == self.attacker;
+
+        loop {
+            // the attacker needs one proven move, the defender has to have all of its moves proven
+            let mut pn = if or_node { INFINITE } else { 0 };
+            let mut dn = if or_node { 0 } else { INFINITE };
+            let mut best = 0;
+            let mut best_value = INFINITE;
+            let mut second_value = INFINITE;
+            let mut best_entry = ProofEntry { pn: 1, dn: 1 };
+
+            for (index, &action) in actions.iter().enumerate() {
+                board.perform_action(action);
+                let entry = self.lookup(board, 3 - next_player, remaining - 1);
+                board.undo_action(action);
+
+                let value = if or_node {
+                    pn = pn.min(entry.pn);
+                    dn = dn.saturating_add(entry.dn).min(INFINITE);
+                    entry.pn
+                } else {
+                    pn = pn.saturating_add(entry.pn).min(INFINITE);
+                    dn = dn.min(entry.dn);
+                    entry.dn
+                };
+                if value < best_value {
+                    second_value = best_value;
+                    best_value = value;
+                    best = index;
+                    best_entry = entry;
+                } else if value < second_value {
+                    second_value = value;
+                }
+            }
+
+            self.store(board, next_player, remaining, ProofEntry { pn, dn });
+            if pn >= th_pn || dn >= th_dn || self.aborted {
+                return;
+            }
+
+            let (child_pn, child_dn) = if or_node {
+                (th_pn.min(second_value.saturating_add(1)), th_dn.saturating_sub(dn).saturating_add(best_entry.dn).min(INFINITE))
+            } else {
+                (th_pn.saturating_sub(pn).saturating_add(best_entry.pn).min(INFINITE), th_dn.min(second_value.saturating_add(1)))
+            };
+
+            let action = actions[best];
+            board.perform_action(action);
+            self.mid(board, 3 - next_player, remaining - 1, child_pn, child_dn);
+            board.undo_action(action);
+        }
+    }
+
+    // a proven line, the attacker's moves keep the proof and the defender's are all lost anyway
+    fn proven_line(&self, board: &Board, remaining: usize) -> Vec<Action> {
+        let mut b = board.clone();
+        let mut player = self.attacker;
+        let mut pv = Vec::new();
+        for r in (1..=remaining).rev() {
+            if b.is_game_over().is_some() {
+                break;
+            }
+            let next = self.actions(&b, player).into_iter().find(|&action| {
+                b.perform_action(action);
+                let entry = self.lookup(&b, 3 - player, r - 1);
+                b.undo_action(action);
+                entry.pn == 0
+            });
+            match next {
+                Some(action) => {
+                    b.perform_action(action);
+                    pv.push(action);
+                    player = 3 - player;
+                },
+                None => break,
+            }
+        }
+        pv
+    }
+}
+
+impl ProofNumberSolver {
+    pub fn new(max_plies: usize) -> ProofNumberSolver {
+        ProofNumberSolver {
+            max_plies,
+            ..Default::default()
+        }
+    }
+
+    // tries 1, 3, 5, ... plies so that a proof is also the shortest win
+    pub fn prove(&self, board: &Board, next_player: usize, limits: &SearchLimits) -> ProofResult {
+        let mut search = ProofSearch {
+            attacker: next_player,
+            limits,
+            table: HashMap::new(),
+            max_entries: self.memory_mb * 1024 * 1024 / (2 * mem::size_of::<((u64, u8), ProofEntry)>()),
+            max_nodes: self.max_nodes,
+            nodes: 0,
+            aborted: false,
+            planner: CampPlanner::default(),
+        };
+        let mut result = ProofResult {
+            status: ProofStatus::Disproven,
+            best_move: None,
+            plies: 0,
+            pv: Vec::new(),
+            nodes: 0,
+        };
+
+        let mut b = board.clone();
+        for plies in (1..=self.max_plies).step_by(2) {
+            search.mid(&mut b, next_player, plies, INFINITE, INFINITE);
+            let entry = search.lookup(&b, next_player, plies);
+            if entry.pn == 0 {
+                result.status = ProofStatus::Proven;
+                result.plies = plies;
+                result.pv = search.proven_line(&b, plies);
+                result.best_move = result.pv.first().copied();
+                break;
+            }
+            if entry.dn != 0 || search.aborted {
+                result.status = ProofStatus::Unknown;
+                break;
+            }
+        }
+
+        result.nodes = search.nodes;
+        result
+    }
+}
+
+impl Solver for ProofNumberSolver {
+    // answers only with proven wins, `NotFound` when the win is disproven or unknown
+    fn solve_with_limits(&self, board: &Board, next_player: usize, limits: &SearchLimits) -> Result<SearchResult, SolverError> {
+        let start = Instant::now();
+        let proof = self.prove(board, next_player, limits);
+        let action = match (proof.status, proof.best_move) {
+            (ProofStatus::Proven, Some(action)) => action,
+            _ => return Err(SolverError::NotFound),
+        };
+
+        let mut result = SearchResult::new(action);
+        result.score = win_in(proof.plies) as f64;
+        result.depth = proof.plies;
+        result.nodes = proof.nodes;
+        result.pv = proof.pv;
+        result.proven = Some(ProvenOutcome::Win);
+        result.moves_to_end = Some((proof.plies as i32 + 1) / 2);
+        result.elapsed = start.elapsed();
+        Ok(result)
+    }
+}
@@ -0,0 +1,90 @@
+use std::time::Instant;
+use crate::board::action::Action;
+use crate::board::board::Board;
+use crate::solver::camp_planner::CampPlanner;
+use crate::solver::search_limits::SearchLimits;
+use crate::solver::solver::{ProvenOutcome, SearchResult, Solver, SolverError};
+
+// exact solver for separated positions, where the game is a pure race
+// each side's minimum number of moves is found by `CampPlanner` with the other army standing still,
+// its pieces block cells and serve as pivots exactly as they do in the position, so the first move is always legal
+pub struct RaceSolver {
+    // per side, the solver gives up with `NotFound` once it is exceeded
+    pub max_nodes: u64,
+}
+
+impl Default for RaceSolver {
+    fn default() -> Self {
+        RaceSolver {
+            max_nodes: 100_000,
+        }
+    }
+}
+
+impl RaceSolver {
+    pub fn new(max_nodes: u64) -> RaceSolver {
+        RaceSolver {
+            max_nodes,
+        }
+    }
+
+    // an optimal sequence of moves filling `player`'s camp, None if the budget ran out
+    pub fn min_moves(&self, board: &Board, player: usize, limits: &SearchLimits, nodes: &mut u64) -> Option<Vec<Action>> {
+        let plan = CampPlanner::new(self.max_nodes).plan(board, player, limits)?;
+        *nodes += plan.nodes;
+        Some(plan.actions)
+    }
+
+    // both plans played in turns from `board` until the game ends, cut short at the first move a plan
+    // made against the other army's starting squares can no longer play
+    pub fn race_line(&self, board: &Board, next_player: usize, ours: &[Action], theirs: &[Action]) -> Vec<Action> {
+        let mut b = board.clone();
+        let mut player = next_player;
+        let mut line = Vec::new();
+        let (mut our_moves, mut their_moves) = (ours.iter(), theirs.iter());
+        while b.is_game_over().is_none() {
+            let next = if player == next_player { our_moves.next() } else { their_moves.next() };
+            let action = match next {
+                Some(&x) if b.generate_actions_all(player).contains(&x) => x,
+                _ => break,
+            };
+            b.perform_action(action);
+            line.push(action);
+            player = 3 - player;
+        }
+        line
+    }
+}
+
+impl Solver for RaceSolver {
+    // `NotFound` unless the position is separable and both races were solved within budget
+    // the side to move wins the race on equal counts
+    fn solve_with_limits(&self, board: &Board, next_player: usize, limits: &SearchLimits) -> Result<SearchResult, SolverError> {
+        let start = Instant::now();
+        if board.is_game_over().is_some() || !board.is_separable() {
+            return Err(SolverError::NotFound);
+        }
+
+        let mut nodes = 0;
+        let ours = self.min_moves(board, next_player, limits, &mut nodes).ok_or(SolverError::NotFound)?;
+        let theirs = self.min_moves(board, 3 - next_player, limits, &mut nodes).ok_or(SolverError::NotFound)?;
+        if ours.is_empty() {
+            return Err(SolverError::NotFound);
+        }
+
+        let mut result = SearchResult::new(ours[0]);
+        result.score = theirs.len() as f64 - ours.len() as f64;
+        result.depth = ours.len();
+        result.nodes = nodes;
+        if ours.len() <= theirs.len() {
+            result.proven = Some(ProvenOutcome::Win);
+            result.moves_to_end = Some(ours.len() as i32);
+        } else {
+            result.proven = Some(ProvenOutcome::Loss);
+            result.moves_to_end = Some(-(theirs.len() as i32));
+        }
+        result.pv = self.race_line(board, next_player, &ours, &theirs);
+        result.elapsed = start.elapsed();
+        Ok(result)
+    }
+}
@@ -0,0 +1,67 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+// cooperative stop flag, clones share the same flag
+#[derive(Clone, Default)]
+pub struct CancellationToken {
+    flag: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    pub fn new() -> CancellationToken {
+        Default::default()
+    }
+
+    pub fn cancel(&self) {
+        self.flag.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.flag.load(Ordering::Relaxed)
+    }
+}
+
+// limits every solver checks while searching, a stopped search returns the best move found so far
+#[derive(Clone, Default)]
+pub struct SearchLimits {
+    pub deadline: Option<Instant>,
+    // nodes for tree searches, iterations for MCTS
+    pub max_nodes: Option<u64>,
+    // caps the solver's own depth setting, never raises it
+    pub max_depth: Option<usize>,
+    pub cancel: CancellationToken,
+}
+
+impl SearchLimits {
+    pub fn with_time(time: Duration) -> SearchLimits {
+        SearchLimits {
+            deadline: Some(Instant::now() + time),
+            ..Default::default()
+        }
+    }
+
+    pub fn depth(&self, depth: usize) -> usize {
+        match self.max_depth {
+            Some(x) => depth.min(x),
+            None => depth,
+        }
+    }
+
+    pub fn should_stop(&self, nodes: u64) -> bool {
+        if self.cancel.is_cancelled() {
+            return true;
+        }
+        if let Some(max_nodes) = self.max_nodes {
+            if nodes >= max_nodes {
+                return true;
+            }
+        }
+        if let Some(deadline) = self.deadline {
+            if Instant::now() >= deadline {
+                return true;
+            }
+        }
+        false
+    }
+}
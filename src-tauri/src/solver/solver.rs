@@ -1,10 +1,80 @@
+use std::fmt::{Display, Formatter};
+use std::time::Duration;
+use serde::{Serialize, Deserialize};
 use crate::board::action::Action;
 use crate::board::board::Board;
+use crate::solver::search_limits::SearchLimits;
 
-pub trait Solver {
-    fn solve(&self, board: &Board, next_player: usize) -> Action;
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub enum ProvenOutcome {
+    Win,
+    Loss,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct SearchResult {
+    pub best_move: Action,
+    // solver specific: evaluation for alpha-beta, win rate of the chosen move for MCTS
+    pub score: f64,
+    pub depth: usize,
+    // nodes searched, MCTS counts iterations
+    pub nodes: u64,
+    pub elapsed: Duration,
+    // starts with `best_move`, single player planners only list the moves of the side to move
+    pub pv: Vec<Action>,
+    // set when the result is exact, seen from the side to move
+    pub proven: Option<ProvenOutcome>,
+    // for proven results: our moves until we win when positive, the opponent's moves until we lose when negative
+    pub moves_to_end: Option<i32>,
+}
+
+impl SearchResult {
+    pub fn new(best_move: Action) -> SearchResult {
+        SearchResult {
+            best_move,
+            score: 0.0,
+            depth: 0,
+            nodes: 0,
+            elapsed: Duration::ZERO,
+            pv: vec![best_move],
+            proven: None,
+            moves_to_end: None,
+        }
+    }
+
+    // "win in 3", "loss in 2" or the plain score
+    pub fn describe(&self) -> String {
+        match self.moves_to_end {
+            Some(n) if n >= 0 => format!("win in {}", n),
+            Some(n) => format!("loss in {}", -n),
+            None => format!("{}", self.score),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub enum SolverError {
+    // the side to move has no legal move
+    NoLegalMoves,
+    // the solver can only answer some positions and this is not one of them
+    NotFound,
+}
+
+impl Display for SolverError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SolverError::NoLegalMoves => write!(f, "no legal moves"),
+            SolverError::NotFound => write!(f, "no move found"),
+        }
+    }
 }
 
-pub trait MaybeSolver {
-    fn solve(&self, board: &Board, next_player: usize) -> Option<Action>;
-}
\ No newline at end of file
+impl std::error::Error for SolverError {}
+
+pub trait Solver {
+    fn solve(&self, board: &Board, next_player: usize) -> Result<SearchResult, SolverError> {
+        self.solve_with_limits(board, next_player, &SearchLimits::default())
+    }
+
+    fn solve_with_limits(&self, board: &Board, next_player: usize, limits: &SearchLimits) -> Result<SearchResult, SolverError>;
+}
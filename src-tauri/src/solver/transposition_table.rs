@@ -0,0 +1,186 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use crate::board::action::Action;
+use crate::evaluation::score::Score;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Bound {
+    Exact,
+    // the real score is at least `score`
+    Lower,
+    // the real score is at most `score`
+    Upper,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct TTEntry {
+    pub best_move: Option<Action>,
+    pub depth: usize,
+    pub bound: Bound,
+    // distance-encoded scores are stored relative to the node, see `score_to_tt`
+    pub score: Score,
+}
+
+impl TTEntry {
+    // layout: score in bits 0..32, move in 32..48, depth in 48..56, bound in 56..58, has-move in 58,
+    // and bit 59 always set so that no entry packs to the 0 of an empty slot
+    fn pack(&self) -> u64 {
+        let mut data = self.score as u32 as u64 | 1 << 59;
+        if let Some(action) = self.best_move {
+            let m = (action.from_x as u64) | (action.from_y as u64) << 4 | (action.to_x as u64) << 8 | (action.to_y as u64) << 12;
+            data |= m << 32;
+            data |= 1 << 58;
+        }
+        data |= (self.depth.min(255) as u64) << 48;
+        let bound = match self.bound {
+            Bound::Exact => 0,
+            Bound::Lower => 1,
+            Bound::Upper => 2,
+        };
+        data | bound << 56
+    }
+
+    fn unpack(data: u64) -> TTEntry {
+        let best_move = if data >> 58 & 1 == 1 {
+            let m = data >> 32;
+            Some(Action {
+                from_x: (m & 15) as u8,
+                from_y: (m >> 4 & 15) as u8,
+                to_x: (m >> 8 & 15) as u8,
+                to_y: (m >> 12 & 15) as u8,
+            })
+        } else {
+            None
+        };
+        let bound = match data >> 56 & 3 {
+            0 => Bound::Exact,
+            1 => Bound::Lower,
+            _ => Bound::Upper,
+        };
+
+        TTEntry {
+            best_move,
+            depth: (data >> 48 & 255) as usize,
+            bound,
+            score: data as u32 as Score,
+        }
+    }
+}
+
+// lock-free table shared by all search threads
+// each slot stores `key ^ data` next to `data`, a torn write from two threads fails the key check and reads as a miss
+pub struct TranspositionTable {
+    slots: Vec<[AtomicU64; 2]>,
+    mask: usize,
+}
+
+impl TranspositionTable {
+    pub fn new(size_log2: usize) -> TranspositionTable {
+        let size = 1_usize << size_log2;
+        TranspositionTable {
+            slots: (0..size).map(|_| [AtomicU64::new(0), AtomicU64::new(0)]).collect(),
+            mask: size - 1,
+        }
+    }
+
+    pub fn probe(&self, key: u64) -> Option<TTEntry> {
+        let slot = &self.slots[key as usize & self.mask];
+        let check = slot[0].load(Ordering::Relaxed);
+        let data = slot[1].load(Ordering::Relaxed);
+        if check ^ data == key && data != 0 {
+            Some(TTEntry::unpack(data))
+        } else {
+            None
+        }
+    }
+
+    // an entry for the same position is only replaced by an equal or deeper search
+    pub fn store(&self, key: u64, entry: TTEntry) {
+        let slot = &self.slots[key as usize & self.mask];
+        if let Some(old) = self.probe(key) {
+            if old.depth > entry.depth {
+                return;
+            }
+        }
+        let data = entry.pack();
+        slot[0].store(key ^ data, Ordering::Relaxed);
+        slot[1].store(data, Ordering::Relaxed);
+    }
+
+    pub fn clear(&self) {
+        for slot in self.slots.iter() {
+            slot[0].store(0, Ordering::Relaxed);
+            slot[1].store(0, Ordering::Relaxed);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::board::action::Action;
+    use crate::evaluation::score::{loss_in, win_in};
+    use super::{Bound, TTEntry, TranspositionTable};
+
+    fn entries() -> Vec<TTEntry> {
+        let mut result = Vec::new();
+        for score in [0, 1, -1, 123, -4567, win_in(3), loss_in(8), i32::MAX, i32::MIN] {
+            for best_move in [None, Some(Action::from_usize(0, 0, 0, 0)), Some(Action::from_usize(15, 3, 7, 15))] {
+                for depth in [0, 1, 17, 255] {
+                    for bound in [Bound::Exact, Bound::Lower, Bound::Upper] {
+                        result.push(TTEntry { best_move, depth, bound, score });
+                    }
+                }
+            }
+        }
+        result
+    }
+
+    fn assert_same(a: TTEntry, b: TTEntry) {
+        assert_eq!(a.best_move, b.best_move);
+        assert_eq!(a.depth, b.depth);
+        assert_eq!(a.bound, b.bound);
+        assert_eq!(a.score, b.score);
+    }
+
+    #[test]
+    fn pack_round_trip() {
+        for entry in entries() {
+            assert_same(TTEntry::unpack(entry.pack()), entry);
+        }
+    }
+
+    #[test]
+    fn store_and_probe() {
+        let tt = TranspositionTable::new(4);
+        for (index, entry) in entries().into_iter().enumerate() {
+            let key = 0x9e37_79b9_7f4a_7c15_u64.wrapping_mul(index as u64 + 1);
+            tt.clear();
+            assert!(tt.probe(key).is_none());
+            tt.store(key, entry);
+            assert_same(tt.probe(key).unwrap(), entry);
+            // same slot, other position
+            assert!(tt.probe(key ^ 1 << 40).is_none());
+        }
+    }
+
+    #[test]
+    fn torn_write_is_a_miss() {
+        let tt = TranspositionTable::new(4);
+        let key = 0x1234_5678_9abc_def0;
+        let entry = TTEntry { best_move: Some(Action::from_usize(1, 2, 3, 4)), depth: 5, bound: Bound::Lower, score: 42 };
+        tt.store(key, entry);
+        // the data word of another entry lands without its check word
+        let other = TTEntry { score: 43, ..entry };
+        let slot = &tt.slots[key as usize & tt.mask];
+        slot[1].store(other.pack(), std::sync::atomic::Ordering::Relaxed);
+        assert!(tt.probe(key).is_none());
+    }
+
+    #[test]
+    fn shallower_entries_do_not_replace() {
+        let tt = TranspositionTable::new(4);
+        let deep = TTEntry { best_move: None, depth: 6, bound: Bound::Exact, score: 10 };
+        tt.store(7, deep);
+        tt.store(7, TTEntry { depth: 2, score: -10, ..deep });
+        assert_same(tt.probe(7).unwrap(), deep);
+    }
+}
@@ -0,0 +1,129 @@
+use std::collections::HashSet;
+use crate::board::board::Board;
+use crate::tablebase::tablebase::{Tablebase, TablebaseIndex, TablebaseValue};
+
+// the generator refuses boards whose tables would need more memory than this
+pub const MAX_GENERATOR_BYTES: usize = 4 << 30;
+
+// memory the generator needs for a board, None if the camps overlap or it does not even fit in a usize
+// values and move counters are two arrays of one byte per position, so 5x5 with a 2x2 camp takes about 300 MB
+// and 6x6 with a 2x2 camp about 8.5 GB
+pub fn generator_bytes(size: usize, camp: usize) -> Option<usize> {
+    if 2 * camp > size {
+        return None;
+    }
+    TablebaseIndex::new(size, camp).checked_len()?.checked_mul(2)
+}
+
+// what `generate_with_progress` reports while it runs
+pub enum GeneratorProgress {
+    // every position has been visited once, terminal ones resolved
+    Terminal { positions: usize, terminal: usize },
+    // positions resolved at the given distance from the end
+    Ply { plies: usize, resolved: usize },
+    // the distance no longer fits the encoding, the positions still on the frontier stay draws
+    DistanceLimit { unresolved: usize },
+}
+
+// retrograde analysis of every position with a full camp of pieces per side
+// terminal positions are resolved first, then values are propagated backwards one ply at a time:
+// a position is won as soon as one move reaches a position lost for the opponent,
+// and lost once every move has been shown to reach a position won for the opponent
+// whatever is left unresolved at the end is a draw
+// None if the board needs more than `MAX_GENERATOR_BYTES`
+pub fn generate(size: usize, camp: usize) -> Option<Tablebase> {
+    generate_with_progress(size, camp, |_| ())
+}
+
+pub fn generate_with_progress(size: usize, camp: usize, mut progress: impl FnMut(GeneratorProgress)) -> Option<Tablebase> {
+    if generator_bytes(size, camp).map_or(true, |x| x > MAX_GENERATOR_BYTES) {
+        return None;
+    }
+    let index = TablebaseIndex::new(size, camp);
+    let total = index.len();
+    let mut values = vec![0_u8; total];
+    // moves of each unresolved position not yet shown to lose
+    let mut remaining = vec![0_u8; total];
+    let mut frontier = Vec::new();
+
+    let mut board = Board::with_camp(size, camp);
+    for i in 0..total {
+        let next_player = index.position(i, &mut board);
+        match board.is_game_over() {
+            Some(winner) => {
+                let value = if winner == next_player { TablebaseValue::Win(0) } else { TablebaseValue::Loss(0) };
+                values[i] = value.encode();
+                frontier.push(i);
+            },
+            None => {
+                let moves = board.generate_actions_all(next_player).len();
+                assert!(moves < 256, "too many moves for the tablebase counters");
+                remaining[i] = moves as u8;
+            },
+        }
+    }
+    progress(GeneratorProgress::Terminal { positions: total, terminal: frontier.len() });
+
+    let mut plies = 0;
+    while !frontier.is_empty() {
+        // the distance has to fit the 7 bits of the encoding, anything longer stays a draw
+        if plies + 1 >= 127 {
+            progress(GeneratorProgress::DistanceLimit { unresolved: frontier.len() });
+            break;
+        }
+
+        let mut next_frontier = Vec::new();
+        let mut moves = HashSet::new();
+        for &i in frontier.iter() {
+            let next_player = index.position(i, &mut board);
+            let value = TablebaseValue::decode(values[i]);
+            let previous_player = 3 - next_player;
+
+            // moves are reversible, so the moves of `previous_player` from here are the ones that led here
+            for x in 0..size {
+                for y in 0..size {
+                    if board.data[x][y] != previous_player {
+                        continue;
+                    }
+                    moves.clear();
+                    board.generate_actions_from_point_single_step(x, y, &mut moves);
+                    board.generate_actions_from_point_jumps(x, y, &mut moves);
+
+                    for &action in moves.iter() {
+                        board.perform_action(action);
+                        let j = index.index(&board, previous_player);
+                        board.undo_action(action);
+                        // resolved already, terminal positions included
+                        if values[j] != 0 {
+                            continue;
+                        }
+
+                        match value {
+                            TablebaseValue::Loss(_) => {
+                                values[j] = value.parent().encode();
+                                next_frontier.push(j);
+                            },
+                            TablebaseValue::Win(_) => {
+                                remaining[j] -= 1;
+                                if remaining[j] == 0 {
+                                    values[j] = value.parent().encode();
+                                    next_frontier.push(j);
+                                }
+                            },
+                            TablebaseValue::Draw => (),
+                        }
+                    }
+                }
+            }
+        }
+
+        plies += 1;
+        progress(GeneratorProgress::Ply { plies, resolved: next_frontier.len() });
+        frontier = next_frontier;
+    }
+
+    Some(Tablebase {
+        index,
+        values,
+    })
+}
@@ -0,0 +1,2 @@
+pub mod tablebase;
+pub mod generator;
@@ -0,0 +1,334 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::Instant;
+use smallvec::{smallvec, SmallVec};
+use crate::board::action::Action;
+use crate::board::board::Board;
+use crate::constants::{BLUE, EMPTY, RED};
+use crate::evaluation::score::{loss_in, moves_to_end, win_in, Score};
+use crate::solver::solver::{ProvenOutcome, SearchResult};
+
+const MAGIC: &[u8; 4] = b"CCTB";
+const VERSION: u8 = 1;
+
+// value of a position for the side to move, distances in plies until a camp is filled
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TablebaseValue {
+    Win(usize),
+    Loss(usize),
+    // neither side can force a win, or a side is left without moves
+    Draw,
+}
+
+impl TablebaseValue {
+    // one byte per position: 0 is a draw, the high bit marks a win, the rest is plies + 1
+    pub fn encode(&self) -> u8 {
+        match *self {
+            TablebaseValue::Draw => 0,
+            TablebaseValue::Win(plies) => 0x80 | (plies + 1) as u8,
+            TablebaseValue::Loss(plies) => (plies + 1) as u8,
+        }
+    }
+
+    pub fn decode(value: u8) -> TablebaseValue {
+        if value == 0 {
+            TablebaseValue::Draw
+        } else if value & 0x80 != 0 {
+            TablebaseValue::Win((value & 0x7f) as usize - 1)
+        } else {
+            TablebaseValue::Loss(value as usize - 1)
+        }
+    }
+
+    // the same value seen from the position before the last move
+    pub fn parent(&self) -> TablebaseValue {
+        match *self {
+            TablebaseValue::Win(plies) => TablebaseValue::Loss(plies + 1),
+            TablebaseValue::Loss(plies) => TablebaseValue::Win(plies + 1),
+            TablebaseValue::Draw => TablebaseValue::Draw,
+        }
+    }
+
+    // alpha-beta score of the value found `ply` plies from the root
+    pub fn score(&self, ply: usize) -> Score {
+        match *self {
+            TablebaseValue::Win(plies) => win_in(ply + plies),
+            TablebaseValue::Loss(plies) => loss_in(ply + plies),
+            TablebaseValue::Draw => 0,
+        }
+    }
+
+    // higher is better for the side to move: quick wins, then draws, then slow losses
+    fn rank(&self) -> i64 {
+        match *self {
+            TablebaseValue::Win(plies) => 1000 - plies as i64,
+            TablebaseValue::Draw => 0,
+            TablebaseValue::Loss(plies) => -1000 + plies as i64,
+        }
+    }
+}
+
+// exact values for every position of a small board where both sides have a full camp worth of pieces
+// positions are indexed by the red piece set, the blue piece set among the remaining cells and the side to move
+// opt-in: no solver loads one by default, tables built with `build_tablebase` are passed to a solver's `tablebase`
+// field, and they only cover boards far smaller than the real game
+pub struct Tablebase {
+    pub index: TablebaseIndex,
+    pub values: Vec<u8>,
+}
+
+// binomial coefficients up to n
+pub struct Binomial {
+    table: Vec<Vec<usize>>,
+}
+
+impl Binomial {
+    pub fn new(n: usize) -> Binomial {
+        let mut table = vec![vec![0_usize; n + 1]; n + 1];
+        for i in 0..=n {
+            table[i][0] = 1;
+            for j in 1..=i {
+                // saturates on boards far too big for a tablebase, so their size can still be checked
+                table[i][j] = table[i - 1][j - 1].saturating_add(table[i - 1][j]);
+            }
+        }
+        Binomial {
+            table,
+        }
+    }
+
+    pub fn get(&self, n: usize, k: usize) -> usize {
+        if k > n {
+            0
+        } else {
+            self.table[n][k]
+        }
+    }
+
+    // colex rank of the sorted set `cells`
+    pub fn rank(&self, cells: &[usize]) -> usize {
+        cells.iter().enumerate().map(|(i, &c)| self.get(c, i + 1)).sum()
+    }
+
+    // inverse of `rank`, fills `cells` in increasing order
+    pub fn unrank(&self, mut rank: usize, cells: &mut [usize]) {
+        for i in (0..cells.len()).rev() {
+            let mut c = i;
+            while self.get(c + 1, i + 1) <= rank {
+                c += 1;
+            }
+            rank -= self.get(c, i + 1);
+            cells[i] = c;
+        }
+    }
+}
+
+// maps positions to table indices and back
+pub struct TablebaseIndex {
+    pub size: usize,
+    pub camp: usize,
+    pub pieces: usize,
+    pub cells: usize,
+    pub binomial: Binomial,
+}
+
+impl TablebaseIndex {
+    pub fn new(size: usize, camp: usize) -> TablebaseIndex {
+        let cells = size * size;
+        TablebaseIndex {
+            size,
+            camp,
+            pieces: camp * camp,
+            cells,
+            binomial: Binomial::new(cells),
+        }
+    }
+
+    pub fn red_sets(&self) -> usize {
+        self.binomial.get(self.cells, self.pieces)
+    }
+
+    pub fn blue_sets(&self) -> usize {
+        self.binomial.get(self.cells - self.pieces, self.pieces)
+    }
+
+    pub fn len(&self) -> usize {
+        self.red_sets() * self.blue_sets() * 2
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // `len` for boards where it may not fit in a usize
+    pub fn checked_len(&self) -> Option<usize> {
+        self.red_sets().checked_mul(self.blue_sets())?.checked_mul(2)
+    }
+
+    pub fn covers(&self, board: &Board) -> bool {
+        if board.size != self.size || board.camp != self.camp {
+            return false;
+        }
+        let mut red = 0;
+        let mut blue = 0;
+        for column in board.data.iter() {
+            for &cell in column.iter() {
+                if cell == RED {
+                    red += 1;
+                } else if cell == BLUE {
+                    blue += 1;
+                }
+            }
+        }
+        red == self.pieces && blue == self.pieces
+    }
+
+    // the board must be covered
+    pub fn index(&self, board: &Board, next_player: usize) -> usize {
+        let mut red: SmallVec<[usize; 16]> = SmallVec::new();
+        let mut blue: SmallVec<[usize; 16]> = SmallVec::new();
+        for c in 0..self.cells {
+            match board.data[c / self.size][c % self.size] {
+                RED => red.push(c),
+                // blue cells are numbered among the cells red leaves free
+                BLUE => blue.push(c - red.len()),
+                _ => (),
+            }
+        }
+
+        let red_rank = self.binomial.rank(&red);
+        let blue_rank = self.binomial.rank(&blue);
+        (red_rank * self.blue_sets() + blue_rank) * 2 + (next_player == BLUE) as usize
+    }
+
+    // writes the position of `index` into `board`, which must have the right size, returns the side to move
+    pub fn position(&self, index: usize, board: &mut Board) -> usize {
+        let next_player = if index % 2 == 1 { BLUE } else { RED };
+        let index = index / 2;
+        let mut red: SmallVec<[usize; 16]> = smallvec![0; self.pieces];
+        let mut blue: SmallVec<[usize; 16]> = smallvec![0; self.pieces];
+        self.binomial.unrank(index / self.blue_sets(), &mut red);
+        self.binomial.unrank(index % self.blue_sets(), &mut blue);
+
+        for column in board.data.iter_mut() {
+            for cell in column.iter_mut() {
+                *cell = EMPTY;
+            }
+        }
+        for &c in red.iter() {
+            board.data[c / self.size][c % self.size] = RED;
+        }
+        // walk the free cells to turn blue's compressed numbers back into cells
+        let mut free = 0;
+        let mut next = 0;
+        for c in 0..self.cells {
+            if next == self.pieces {
+                break;
+            }
+            if board.data[c / self.size][c % self.size] == RED {
+                continue;
+            }
+            if free == blue[next] {
+                board.data[c / self.size][c % self.size] = BLUE;
+                next += 1;
+            }
+            free += 1;
+        }
+        next_player
+    }
+}
+
+impl Tablebase {
+    pub fn covers(&self, board: &Board) -> bool {
+        self.index.covers(board)
+    }
+
+    pub fn probe(&self, board: &Board, next_player: usize) -> Option<TablebaseValue> {
+        if !self.index.covers(board) {
+            return None;
+        }
+        Some(TablebaseValue::decode(self.values[self.index.index(board, next_player)]))
+    }
+
+    // the best move by the table and the value it keeps, None if the position is not covered or is over
+    pub fn best_move(&self, board: &Board, next_player: usize) -> Option<(Action, TablebaseValue)> {
+        if !self.covers(board) || board.is_game_over().is_some() {
+            return None;
+        }
+        let mut actions = board.generate_actions_all(next_player);
+        actions.sort_by_key(|a| (a.from_x, a.from_y, a.to_x, a.to_y));
+
+        let mut b = board.clone();
+        let mut best: Option<(Action, TablebaseValue)> = None;
+        for &action in actions.iter() {
+            b.perform_action(action);
+            let value = self.probe(&b, 3 - next_player).unwrap().parent();
+            b.undo_action(action);
+            if best.map_or(true, |(_, x)| value.rank() > x.rank()) {
+                best = Some((action, value));
+            }
+        }
+        best
+    }
+
+    // a complete answer for a covered position, the pv follows the table until the game ends
+    pub fn solve(&self, board: &Board, next_player: usize) -> Option<SearchResult> {
+        let start = Instant::now();
+        let (action, value) = self.best_move(board, next_player)?;
+
+        let mut result = SearchResult::new(action);
+        let score = value.score(0);
+        result.score = score as f64;
+        result.moves_to_end = moves_to_end(score);
+        result.proven = match value {
+            TablebaseValue::Win(_) => Some(ProvenOutcome::Win),
+            TablebaseValue::Loss(_) => Some(ProvenOutcome::Loss),
+            TablebaseValue::Draw => None,
+        };
+
+        if value != TablebaseValue::Draw {
+            let mut b = board.clone();
+            let mut player = next_player;
+            b.perform_action(action);
+            player = 3 - player;
+            while let Some((next, _)) = self.best_move(&b, player) {
+                result.pv.push(next);
+                b.perform_action(next);
+                player = 3 - player;
+            }
+        }
+        result.depth = result.pv.len();
+        result.elapsed = start.elapsed();
+        Some(result)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let mut data = Vec::with_capacity(self.values.len() + 7);
+        data.extend_from_slice(MAGIC);
+        data.push(VERSION);
+        data.push(self.index.size as u8);
+        data.push(self.index.camp as u8);
+        data.extend_from_slice(&self.values);
+        fs::write(path, data)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Tablebase> {
+        let data = fs::read(path)?;
+        if data.len() < 7 || &data[0..4] != MAGIC || data[4] != VERSION {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "not a tablebase file"));
+        }
+        let size = data[5] as usize;
+        let camp = data[6] as usize;
+        let index = TablebaseIndex::new(size, camp);
+        let values = data[7..].to_vec();
+        if values.len() != index.len() {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "truncated tablebase file"));
+        }
+
+        Ok(Tablebase {
+            index,
+            values,
+        })
+    }
+}